@@ -0,0 +1,126 @@
+//! Local stand-in for the mclo.gs API, for testing `share_game_log` without
+//! uploading anything.
+//!
+//! ```sh
+//! cargo run --example paste_server            # listens on 127.0.0.1:8085
+//! LOG_SHARE_URL=http://127.0.0.1:8085/1/log   # point the launcher at it
+//! ```
+//!
+//! Uploaded logs are kept in memory and served back at the returned URL.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+type Store = Arc<Mutex<HashMap<String, String>>>;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8085".to_string());
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Paste stand-in listening on http://{}/1/log", addr);
+
+    let store: Store = Arc::new(Mutex::new(HashMap::new()));
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let store = Arc::clone(&store);
+        let addr = addr.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &addr, store).await {
+                eprintln!("Request failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle(stream: TcpStream, addr: &str, store: Store) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let (status, content_type, response) = match (method.as_str(), path.as_str()) {
+        ("POST", "/1/log") => {
+            let content = String::from_utf8_lossy(&body)
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("content=").map(url_decode))
+                .unwrap_or_default();
+            if content.is_empty() {
+                ("400 Bad Request", "application/json", r#"{"success":false,"error":"Required field 'content' not found."}"#.to_string())
+            } else {
+                let mut logs = store.lock().unwrap();
+                let id = format!("{:06}", logs.len() + 1);
+                println!("Stored log {} ({} bytes)", id, content.len());
+                logs.insert(id.clone(), content);
+                (
+                    "200 OK",
+                    "application/json",
+                    format!(r#"{{"success":true,"id":"{0}","url":"http://{1}/{0}","raw":"http://{1}/{0}"}}"#, id, addr),
+                )
+            }
+        }
+        ("GET", p) => match store.lock().unwrap().get(p.trim_start_matches('/')) {
+            Some(content) => ("200 OK", "text/plain; charset=utf-8", content.clone()),
+            None => ("404 Not Found", "text/plain", "Not found".to_string()),
+        },
+        _ => ("404 Not Found", "text/plain", "Not found".to_string()),
+    };
+
+    let mut stream = reader.into_inner();
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                content_type,
+                response.len(),
+                response
+            )
+            .as_bytes(),
+        )
+        .await
+}
+
+/// Decodes an `application/x-www-form-urlencoded` value.
+fn url_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match u8::from_str_radix(&value[i + 1..i + 3], 16) {
+                    Ok(b) => out.push(b),
+                    Err(_) => out.extend_from_slice(&bytes[i..i + 3]),
+                }
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,532 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use log::{info, warn, error};
+use tauri::{AppHandle, Manager, Url};
+
+use crate::core;
+use crate::core::paths::{self, LocationMode};
+use crate::database::DbManager;
+use crate::models::options::{GameOptions, LauncherOptions};
+use crate::models::validation::FieldError;
+use crate::services::auth_service;
+use crate::services::deep_link;
+use crate::services::event_sink::{ConsoleSink, EventSink};
+use crate::services::game_service::{AuthMode, GameManager};
+use crate::services::game_session::GameSession;
+use crate::services::instance::{self, Instance};
+use crate::services::java_installer;
+use crate::services::options_migration::{self, OptionsFile};
+use crate::services::options_repository::OptionsRepository;
+use crate::services::process_priority;
+use crate::services::server_status_service;
+use crate::services::session_service::SessionService;
+
+/// Permadeath launcher. Without a subcommand, the launcher window opens.
+#[derive(Parser)]
+#[command(name = "permadeath-launcher", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+    /// `permadeath://` link the desktop opened the launcher with; handled by the UI
+    #[arg(hide = true)]
+    pub url: Option<String>,
+}
+
+impl Cli {
+    /// Parses the process arguments, exiting with a usage message on error.
+    pub fn from_env() -> Self {
+        Self::parse()
+    }
+}
+
+#[derive(Subcommand)]
+pub enum CliCommand {
+    /// Launch the game without opening the launcher window
+    Launch {
+        /// Launch profile to use
+        #[arg(long, default_value = crate::services::game_service::DEFAULT_PROFILE)]
+        profile: String,
+        /// Play offline instead of with the Microsoft account
+        #[arg(long)]
+        offline: bool,
+        /// In-game name for offline mode (defaults to the logged-in launcher account)
+        #[arg(long)]
+        username: Option<String>,
+        /// Print the command that would run instead of starting the game
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show whether the server is online and who is playing
+    Status,
+    /// Log in with a launcher account and remember the session
+    Login {
+        #[arg(long)]
+        username: Option<String>,
+    },
+    /// Check the installation for common problems
+    Doctor,
+    /// Print the log of a game session
+    Logs {
+        /// Session to print (defaults to the most recent one)
+        #[arg(long)]
+        session: Option<String>,
+        /// List the recorded sessions instead
+        #[arg(long)]
+        list: bool,
+        /// Only print the last N lines
+        #[arg(long)]
+        tail: Option<usize>,
+    },
+}
+
+/// Runs a headless subcommand and returns the process exit code.
+#[tokio::main]
+pub async fn run(command: CliCommand) -> i32 {
+    if let Err(e) = core::logger::setup_file_logger() {
+        eprintln!("Error while setting up the logger: {}", e);
+    }
+    dotenvy::dotenv().ok();
+
+    let result = match command {
+        CliCommand::Launch { profile, offline, username, dry_run: true } => preview(&profile, offline, username).await,
+        CliCommand::Launch { profile, offline, username, .. } => launch(&profile, offline, username).await,
+        CliCommand::Status => status().await,
+        CliCommand::Login { username } => login(username).await,
+        CliCommand::Doctor => doctor().await,
+        CliCommand::Logs { session, list, tail } => logs(session, list, tail),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            error!("{}", e);
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Subcommands
+// ---------------------------------------------------------------------------
+
+async fn launch(profile: &str, offline: bool, username: Option<String>) -> Result<i32, String> {
+    GameManager::check_profile(profile)?;
+
+    // With the launcher already open, the game is started by that instance instead
+    match instance::acquire(&OptionsRepository::load_launcher_options()) {
+        Ok(Instance::Primary(guard)) => {
+            tokio::spawn(guard.serve(|_| async {
+                Err("A game started from the command line is running. Stop it before opening the launcher again.".to_string())
+            }));
+        }
+        Ok(Instance::Forwarded(reply)) => {
+            println!("{}", reply?);
+            return Ok(0);
+        }
+        Err(e) => warn!("Single-instance check skipped: {}", e),
+    }
+
+    let username = resolve_username(username, offline).await?;
+
+    info!("Headless launch: profile='{}' user='{}' offline={}", profile, username, offline);
+
+    let manager = GameManager::new();
+    let console = ConsoleSink::new();
+    manager.launch(username, !offline, EventSink::Console(console.clone())).await?;
+
+    let mut stop_requested = false;
+    loop {
+        if let Some(code) = console.exit_code() {
+            return Ok(if code < 0 { 1 } else { code });
+        }
+        tokio::select! {
+            _ = tokio::signal::ctrl_c(), if !stop_requested => {
+                println!("[launcher] Stopping the game...");
+                stop_requested = true;
+                manager.kill().await;
+            }
+            _ = tokio::time::sleep(Duration::from_millis(250)) => {}
+        }
+    }
+}
+
+async fn preview(profile: &str, offline: bool, username: Option<String>) -> Result<i32, String> {
+    let username = resolve_username(username, offline).await?;
+    let preview = GameManager::preview(&username, !offline, profile)?;
+
+    let describe = |path: &std::path::Path, present: bool, missing: &str| {
+        if present { path.display().to_string() } else { format!("{} ({})", path.display(), missing) }
+    };
+
+    println!("Profile:     {}", preview.profile);
+    println!("Java:        {}", match &preview.java {
+        Some(java) => java.display().to_string(),
+        None => "not found on PATH".to_string(),
+    });
+    println!("JAR:         {}", describe(&preview.jar_path, preview.jar_present, "downloaded on launch"));
+    println!("Game dir:    {}", preview.game_dir.display());
+    println!("Mods:        {}", preview.mods_manifest_url.as_deref().unwrap_or("none (MODS_JSON_URL is not set)"));
+    println!("Auth:        {}", match &preview.auth {
+        AuthMode::Offline { username } => format!("offline as '{}'", username),
+        AuthMode::Microsoft => "Microsoft account (stored token)".to_string(),
+        AuthMode::MicrosoftLogin => "Microsoft account (browser login)".to_string(),
+    });
+    println!("Max RAM:     {} MB", preview.max_ram_mb);
+    println!("JVM flags:   {}", preview.jvm_flags.join(" "));
+    println!("\n{}", preview.command_line);
+    Ok(0)
+}
+
+async fn status() -> Result<i32, String> {
+    let (host, port) = server_status_service::configured_server();
+    let status = server_status_service::get_server_status(&host, port).await;
+
+    if !status.online {
+        println!("Server {}:{} is offline.", host, port);
+        return Ok(1);
+    }
+
+    println!("Server {}:{} is online ({})", host, port, status.version);
+    if !status.motd.is_empty() {
+        println!("  {}", status.motd.replace('\n', "\n  "));
+    }
+    println!("Players: {}/{}", status.players_online, status.players_max);
+    for name in &status.player_names {
+        println!("  - {}", name);
+    }
+    Ok(0)
+}
+
+async fn login(username: Option<String>) -> Result<i32, String> {
+    let username = match username {
+        Some(u) => u,
+        None => prompt("Username: ")?,
+    };
+    let password = prompt_password("Password: ")?;
+
+    let db_url = database_url()?;
+    let db = DbManager::new(&db_url)
+        .await
+        .map_err(|e| format!("Could not connect to the database: {e}"))?;
+    let session_service = sqlx::MySqlPool::connect(&db_url)
+        .await
+        .map(SessionService::new)
+        .map_err(|e| format!("Could not connect to the database: {e}"))?;
+
+    auth_service::login(&username, &password, &db, &session_service).await?;
+    println!("Logged in as {}.", username);
+    Ok(0)
+}
+
+async fn doctor() -> Result<i32, String> {
+    let options = OptionsRepository::load_launcher_options();
+    let mut failures = 0;
+    let mut report = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("[ok] {}: {}", name, detail),
+        Err(detail) => {
+            failures += 1;
+            println!("[!!] {}: {}", name, detail);
+        }
+    };
+
+    report("Java 21", if java_installer::check_java_version("21") {
+        Ok("installed".to_string())
+    } else {
+        Err("`java -version` does not report Java 21".to_string())
+    });
+
+    let launcher_dir = options.launcher_dir.clone();
+    let mode = match paths::locations().mode {
+        LocationMode::Default => "",
+        LocationMode::Relocated => " (relocated)",
+        LocationMode::Portable => " (portable)",
+    };
+    report("Launcher directory", match &launcher_dir {
+        Some(dir) if dir.is_dir() => Ok(format!("{}{}", dir.display(), mode)),
+        Some(dir) => Err(format!("{} does not exist", dir.display())),
+        None => Err("not configured".to_string()),
+    });
+
+    if let Some(dir) = &launcher_dir {
+        for file in [OptionsFile::Launcher, OptionsFile::Game] {
+            let path = dir.join(file.file_name());
+            let result = match file {
+                OptionsFile::Launcher => check_json(file, &path, LauncherOptions::validate),
+                OptionsFile::Game => check_json(file, &path, GameOptions::validate),
+            };
+            report(file.file_name(), result);
+        }
+    }
+
+    report("Game directory", match &options.game_dir {
+        Some(dir) if dir.is_dir() => Ok(dir.display().to_string()),
+        Some(dir) => Ok(format!("{} (will be created on first launch)", dir.display())),
+        None => Err("not configured".to_string()),
+    });
+
+    let game_options = OptionsRepository::load_game_options(&options);
+    report("Process priority", process_priority::validate(game_options.get_process_priority())
+        .map(|_| "allowed".to_string()));
+
+    let jar = GameManager::get_jar_path();
+    report("Game manager JAR", if jar.exists() {
+        Ok(jar.display().to_string())
+    } else if std::env::var("CLI_JAR_URL").is_ok() {
+        Ok("not downloaded yet, will be fetched on first launch".to_string())
+    } else {
+        Err(format!("{} is missing and CLI_JAR_URL is not set", jar.display()))
+    });
+
+    report("Database", match database_url() {
+        Ok(url) => match tokio::time::timeout(Duration::from_secs(5), sqlx::MySqlPool::connect(&url)).await {
+            Ok(Ok(_)) => Ok("reachable".to_string()),
+            Ok(Err(e)) => Err(format!("connection failed: {}", e)),
+            Err(_) => Err("connection timed out".to_string()),
+        },
+        Err(e) => Err(e),
+    });
+
+    let (host, port) = server_status_service::configured_server();
+    let server = server_status_service::get_server_status(&host, port).await;
+    report("Minecraft server", if server.online {
+        Ok(format!("{}:{} online, {}", host, port, server.version))
+    } else {
+        Err(format!("{}:{} is not reachable", host, port))
+    });
+
+    if failures == 0 {
+        println!("\nEverything looks good.");
+        Ok(0)
+    } else {
+        println!("\n{} problem(s) found.", failures);
+        Ok(1)
+    }
+}
+
+fn logs(session: Option<String>, list: bool, tail: Option<usize>) -> Result<i32, String> {
+    let options = OptionsRepository::load_launcher_options();
+    let sessions = GameSession::list(&options);
+
+    if list {
+        if sessions.is_empty() {
+            println!("No game sessions recorded yet.");
+        }
+        for s in &sessions {
+            println!("{}  {:>10} bytes", s.id, s.log_size);
+        }
+        return Ok(0);
+    }
+
+    let selected = match session {
+        Some(id) => sessions
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Session '{}' was not found. Use --list to see the recorded sessions.", id))?,
+        None => sessions
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No game sessions recorded yet.".to_string())?,
+    };
+
+    let content = std::fs::read_to_string(&selected.log_path)
+        .map_err(|e| format!("Could not read {}: {}", selected.log_path.display(), e))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let start = tail.map(|n| lines.len().saturating_sub(n)).unwrap_or(0);
+    for line in &lines[start..] {
+        println!("{}", line);
+    }
+    Ok(0)
+}
+
+// ---------------------------------------------------------------------------
+// Forwarded arguments
+// ---------------------------------------------------------------------------
+
+/// Handles the arguments of a second launcher start inside the launcher window
+/// that is already open: links go through the usual confirmation and `launch`
+/// starts the game with this instance's `GameManager`.
+pub async fn forward_to_ui(app: AppHandle, args: Vec<String>) -> Result<String, String> {
+    let cli = Cli::try_parse_from(std::iter::once("permadeath-launcher".to_string()).chain(args))
+        .map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    match (cli.command, cli.url) {
+        (Some(CliCommand::Launch { dry_run: true, .. }), _) => Err("Dry runs do not need the launcher window.".to_string()),
+        (Some(CliCommand::Launch { profile, offline, username, .. }), _) => {
+            GameManager::check_profile(&profile)?;
+            let username = resolve_username(username, offline).await?;
+            app.state::<GameManager>()
+                .launch(username, !offline, EventSink::App(app.clone()))
+                .await?;
+            Ok("The game was started by the launcher that is already open.".to_string())
+        }
+        (Some(_), _) => Err("This command does not need the launcher window.".to_string()),
+        (None, Some(url)) => {
+            let url = Url::parse(&url).map_err(|e| format!("Invalid link '{}': {}", url, e))?;
+            deep_link::handle_urls(&app, vec![url]);
+            Ok("The link was opened in the launcher.".to_string())
+        }
+        (None, None) => Ok("The launcher is already open.".to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn database_url() -> Result<String, String> {
+    std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is not defined in .env".to_string())
+}
+
+/// In-game name for a launch: the one given, or else the logged-in launcher account.
+/// Microsoft launches take the name from the account, so none is required.
+async fn resolve_username(username: Option<String>, offline: bool) -> Result<String, String> {
+    match username {
+        Some(u) => Ok(u),
+        None => match saved_session_username().await {
+            Some(u) => Ok(u),
+            None if offline => Err("No launcher session found. Pass --username or run `login` first.".to_string()),
+            None => Ok(String::new()),
+        },
+    }
+}
+
+/// Username of the launcher account stored in the keyring, if its session is still valid.
+async fn saved_session_username() -> Option<String> {
+    let token = SessionService::get_token_from_keyring().ok()??;
+    let pool = sqlx::MySqlPool::connect(&database_url().ok()?).await.ok()?;
+    SessionService::new(pool)
+        .validate_token(&token)
+        .await
+        .ok()?
+        .map(|(_, username)| username)
+}
+
+/// Checks an options file the way loading reads it: migrated to the current
+/// schema (in memory only), then validated.
+fn check_json<T: serde::de::DeserializeOwned>(
+    file: OptionsFile,
+    path: &std::path::Path,
+    validate: impl FnOnce(&T) -> Result<(), Vec<FieldError>>,
+) -> Result<String, String> {
+    let data = std::fs::read_to_string(path).map_err(|_| "missing, defaults will be used".to_string())?;
+    let mut value: serde_json::Value =
+        serde_json::from_str(&data).map_err(|e| format!("cannot be parsed ({})", e))?;
+    let version = options_migration::migrate(file, &mut value).map_err(|e| format!("cannot be upgraded ({})", e))?;
+    let options = serde_json::from_value::<T>(value).map_err(|e| format!("cannot be parsed ({})", e))?;
+    validate(&options).map_err(|errors| {
+        let details: Vec<String> = errors.iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+        format!("invalid settings ({})", details.join(" "))
+    })?;
+    let current = file.current_version();
+    Ok(match version {
+        v if v < current => format!("valid (schema {}, upgraded to {} on load)", v, current),
+        v if v > current => format!("valid (schema {} from a newer launcher, read as {})", v, current),
+        _ => "valid".to_string(),
+    })
+}
+
+fn prompt(label: &str) -> Result<String, String> {
+    print!("{}", label);
+    let _ = std::io::stdout().flush();
+    let mut input = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut input)
+        .map_err(|e| format!("Could not read input: {e}"))?;
+    Ok(input.trim().to_string())
+}
+
+/// Like `prompt`, but the terminal does not echo what is typed, and only the
+/// line ending is removed: spaces are part of the password.
+fn prompt_password(label: &str) -> Result<String, String> {
+    print!("{}", label);
+    let _ = std::io::stdout().flush();
+    let echo = EchoOff::new();
+    let mut input = String::new();
+    let result = std::io::stdin().lock().read_line(&mut input);
+    drop(echo);
+    // The Enter key was not echoed either
+    println!();
+    result.map_err(|e| format!("Could not read input: {e}"))?;
+    Ok(input.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Turns off terminal echo on stdin until dropped. Does nothing when stdin
+/// is not a terminal (piped input).
+struct EchoOff {
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+    #[cfg(windows)]
+    saved: Option<u32>,
+}
+
+#[cfg(unix)]
+impl EchoOff {
+    fn new() -> Self {
+        unsafe {
+            let mut term: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut term) != 0 {
+                return Self { saved: None };
+            }
+            let saved = term;
+            term.c_lflag &= !libc::ECHO;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &term) != 0 {
+                return Self { saved: None };
+            }
+            Self { saved: Some(saved) }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl EchoOff {
+    fn new() -> Self {
+        use windows_sys::Win32::System::Console::{
+            GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_ECHO_INPUT, STD_INPUT_HANDLE,
+        };
+
+        unsafe {
+            let handle = GetStdHandle(STD_INPUT_HANDLE);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                return Self { saved: None };
+            }
+            if SetConsoleMode(handle, mode & !ENABLE_ECHO_INPUT) == 0 {
+                return Self { saved: None };
+            }
+            Self { saved: Some(mode) }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Console::{GetStdHandle, SetConsoleMode, STD_INPUT_HANDLE};
+
+        if let Some(mode) = self.saved {
+            unsafe {
+                SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), mode);
+            }
+        }
+    }
+}
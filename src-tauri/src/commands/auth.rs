@@ -12,37 +12,7 @@ pub async fn login_user(
     db: tauri::State<'_, DbManager>,
     session_service: tauri::State<'_, SessionService>,
 ) -> Result<String, String> {
-    auth_service::validate_login_input(&username, &password)?;
-
-    info!("Attempting to authenticate user: {}", username);
-
-    match db.get_user_by_username(&username).await {
-        Ok(Some(user)) => {
-            if auth_service::verify_password(&password, &user.password_hash)? {
-                match session_service.create_session(user.id).await {
-                    Ok(token) => {
-                        info!("Successful login for '{}', token created", username);
-                        Ok(token)
-                    }
-                    Err(e) => {
-                        error!("Error creating session for '{}': {}", username, e);
-                        Err("Error creating session.".to_string())
-                    }
-                }
-            } else {
-                info!("Incorrect password for user '{}'", username);
-                Err("Invalid credentials.".to_string())
-            }
-        },
-        Ok(None) => {
-            info!("User '{}' not found.", username);
-            Err("Invalid credentials.".to_string())
-        },
-        Err(e) => {
-            error!("Database error during login: {}", e);
-            Err("Invalid credentials.".to_string())
-        }
-    }
+    auth_service::login(&username, &password, &db, &session_service).await
 }
 
 #[tauri::command]
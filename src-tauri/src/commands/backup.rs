@@ -0,0 +1,52 @@
+use log::info;
+
+use crate::services::backup_service::{self, BackupInfo, BackupVerification};
+use crate::services::game_dir_mover;
+use crate::services::game_service::GameManager;
+use crate::services::options_repository::OptionsRepository;
+
+/// Backups in `launcher_dir/backups`, newest first.
+#[tauri::command]
+pub async fn list_backups() -> Vec<BackupInfo> {
+    let opts = OptionsRepository::load_launcher_options();
+    tokio::task::spawn_blocking(move || backup_service::list_backups(&opts))
+        .await
+        .unwrap_or_default()
+}
+
+/// Checks every file of a backup against the hashes stored in its manifest.
+#[tauri::command]
+pub async fn verify_backup(name: String) -> Result<BackupVerification, String> {
+    info!("verify_backup: {}", name);
+    let opts = OptionsRepository::load_launcher_options();
+    tokio::task::spawn_blocking(move || backup_service::verify_backup(&opts, &name))
+        .await
+        .map_err(|e| format!("Verification failed: {e}"))?
+}
+
+/// Restores a whole backup, or only `world`, into the game directory after
+/// taking a safety backup of the current files. Returns the safety backup's name.
+#[tauri::command]
+pub async fn restore_backup(
+    name: String,
+    world: Option<String>,
+    game_manager: tauri::State<'_, GameManager>,
+) -> Result<Option<String>, String> {
+    info!("restore_backup: {} (world: {:?})", name, world);
+    let guard = game_manager.while_idle(|_| {
+        if game_dir_mover::in_progress() {
+            return Err("The game directory is being moved. Wait for it to finish.".to_string());
+        }
+        backup_service::begin_restore()
+    })?;
+
+    let opts = OptionsRepository::load_launcher_options();
+    let game_dir = GameManager::get_game_dir(&opts);
+    tokio::task::spawn_blocking(move || {
+        let snapshot = backup_service::restore_backup(&guard, &opts, &game_dir, &name, world.as_deref())?;
+        backup_service::prune(&opts);
+        Ok(snapshot)
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {e}"))?
+}
@@ -0,0 +1,11 @@
+use crate::services::deep_link::{DeepLinkAction, PendingDeepLink};
+
+/// Returns the deep link waiting for confirmation, if any, and clears it.
+/// Called by the UI once it has loaded, since a link that started the launcher
+/// arrives before the `deep-link` event can be received.
+#[tauri::command]
+pub fn take_pending_deep_link(
+    pending: tauri::State<'_, PendingDeepLink>,
+) -> Option<DeepLinkAction> {
+    pending.take()
+}
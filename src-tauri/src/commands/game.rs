@@ -1,8 +1,15 @@
-use log::info;
+use log::{info, warn};
 use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
-use crate::services::game_service::{GameManager, GameState};
+use crate::services::event_sink::EventSink;
+use crate::services::game_service::{GameManager, GameState, LaunchPreview, DEFAULT_PROFILE};
+use crate::services::game_session::{GameSession, SessionSummary};
+use crate::services::jvm_diagnostics::{self, DiagnosticCapture, DiagnosticKind};
+use crate::services::log_search::{self, LogSearchQuery, LogSearchResult};
+use crate::services::log_share_service;
 use crate::services::options_repository::OptionsRepository;
+use crate::services::process_metrics::GameMetrics;
 use crate::services::server_status_service::{self, ServerStatus};
 
 /// Returns true if the CLI's config.properties already has a stored Microsoft token,
@@ -21,7 +28,19 @@ pub async fn launch_game(
     game_manager: tauri::State<'_, GameManager>,
 ) -> Result<(), String> {
     info!("launch_game: user='{}' microsoft={}", username, use_microsoft);
-    game_manager.launch(username, use_microsoft, app).await
+    game_manager.launch(username, use_microsoft, EventSink::App(app)).await
+}
+
+/// Resolves what `launch_game` would run (Java, JAR, mods, auth and every
+/// argument) without starting anything. Secrets are redacted.
+#[tauri::command]
+pub fn preview_launch(
+    username: String,
+    use_microsoft: bool,
+    profile: Option<String>,
+) -> Result<LaunchPreview, String> {
+    let profile = profile.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    GameManager::preview(&username, use_microsoft, &profile)
 }
 
 #[tauri::command]
@@ -40,14 +59,81 @@ pub fn get_game_state(
     game_manager.get_state()
 }
 
+/// Latest resource sample of the running game, or `None` when nothing is running.
+#[tauri::command]
+pub fn get_game_metrics(
+    game_manager: tauri::State<'_, GameManager>,
+) -> Option<GameMetrics> {
+    game_manager.get_metrics()
+}
+
+/// Captures a thread dump, heap summary or class histogram from the running game
+/// and saves it into the session's folder.
+#[tauri::command]
+pub async fn capture_game_diagnostic(
+    kind: DiagnosticKind,
+    game_manager: tauri::State<'_, GameManager>,
+) -> Result<DiagnosticCapture, String> {
+    info!("capture_game_diagnostic: {:?}", kind);
+    let running = game_manager
+        .get_running()
+        .ok_or_else(|| "The game is not running.".to_string())?;
+    jvm_diagnostics::capture(kind, running.pid, running.session.dir()).await
+}
+
+/// Recorded game sessions, newest first.
+#[tauri::command]
+pub fn list_game_sessions() -> Vec<SessionSummary> {
+    let opts = OptionsRepository::load_launcher_options();
+    GameSession::list(&opts)
+}
+
+/// Searches a session log (the running one by default) and returns one page of matches.
+#[tauri::command]
+pub async fn search_game_log(query: LogSearchQuery) -> Result<LogSearchResult, String> {
+    let opts = OptionsRepository::load_launcher_options();
+    tokio::task::spawn_blocking(move || log_search::search(&opts, &query))
+        .await
+        .map_err(|e| format!("Log search failed: {e}"))?
+}
+
+/// Uploads a redacted session log to the paste service and copies the URL to the clipboard.
+/// Without `session_id`, shares the running session or else the most recent one.
+#[tauri::command]
+pub async fn share_game_log(
+    session_id: Option<String>,
+    app: AppHandle,
+    game_manager: tauri::State<'_, GameManager>,
+) -> Result<String, String> {
+    info!("share_game_log: session={:?}", session_id);
+    let opts = OptionsRepository::load_launcher_options();
+
+    let log_path = match session_id {
+        Some(id) => GameSession::find(&opts, &id)
+            .map(|s| s.log_path)
+            .ok_or_else(|| format!("Session '{}' was not found.", id))?,
+        None => match game_manager.get_running() {
+            Some(running) => running.session.log_path(),
+            None => GameSession::list(&opts)
+                .into_iter()
+                .next()
+                .map(|s| s.log_path)
+                .ok_or_else(|| "There is no game log to share yet.".to_string())?,
+        },
+    };
+
+    let url = log_share_service::share_log(&log_path).await?;
+
+    if let Err(e) = app.clipboard().write_text(url.clone()) {
+        warn!("Could not copy the log URL to the clipboard: {}", e);
+    }
+
+    Ok(url)
+}
+
 #[tauri::command]
 pub async fn get_server_status() -> ServerStatus {
-    let host = std::env::var("MINECRAFT_SERVER_IP")
-        .unwrap_or_else(|_| "localhost".to_string());
-    let port: u16 = std::env::var("MINECRAFT_SERVER_PORT")
-        .unwrap_or_else(|_| "25565".to_string())
-        .parse()
-        .unwrap_or(25565);
+    let (host, port) = server_status_service::configured_server();
     info!("Pinging server at {}:{}", host, port);
     server_status_service::get_server_status(&host, port).await
 }
@@ -1,3 +1,5 @@
 pub mod auth;
 pub mod options;
 pub mod game;
+pub mod deep_link;
+pub mod backup;
@@ -1,7 +1,15 @@
 use log::info;
 
+use crate::models::jvm_flags::{self, FlagInfo, JvmPreset};
 use crate::models::options::{LauncherOptions, GameOptions, GarbageCollector, BASE_VM_FLAGS};
+use crate::models::validation::OptionsError;
+use crate::services::options_migration::{self, OptionsNotice};
+use crate::services::backup_service;
+use crate::services::event_sink::EventSink;
+use crate::services::game_dir_mover::{self, MoveOutcome};
+use crate::services::game_service::GameManager;
 use crate::services::options_repository::OptionsRepository;
+use crate::services::settings_bundle::{self, SettingsImport};
 
 #[tauri::command]
 pub fn read_options() -> LauncherOptions {
@@ -11,12 +19,50 @@ pub fn read_options() -> LauncherOptions {
     options
 }
 
+/// Validates and saves the launcher options. Rejected values come back as
+/// field errors and nothing is written.
 #[tauri::command]
-pub fn save_options(options: LauncherOptions) -> bool {
+pub fn save_options(options: LauncherOptions) -> Result<(), OptionsError> {
     info!("Saving options: {:?}", options);
-    OptionsRepository::save_launcher_options(&options);
+    options.validate()?;
+    OptionsRepository::save_launcher_options(&options).map_err(|message| OptionsError::Io { message })?;
     info!("Options saved correctly");
-    true
+    Ok(())
+}
+
+/// Moves the game files to `target` and saves it as the new `game_dir`,
+/// reporting `game-dir-move-progress` events. With `keep_source`, the old
+/// folder is left as it is.
+#[tauri::command]
+pub async fn move_game_dir(
+    target: String,
+    keep_source: bool,
+    app: tauri::AppHandle,
+    game_manager: tauri::State<'_, GameManager>,
+) -> Result<MoveOutcome, String> {
+    info!("move_game_dir: {} (keep source: {})", target, keep_source);
+    let guard = game_manager.while_idle(|_| {
+        if backup_service::restore_in_progress() {
+            return Err("A backup is being restored. Wait for it to finish.".to_string());
+        }
+        game_dir_mover::begin()
+    })?;
+
+    let mut opts = OptionsRepository::load_launcher_options();
+    let from = GameManager::get_game_dir(&opts);
+    let to = std::path::PathBuf::from(target);
+    let events = EventSink::App(app);
+    tokio::task::spawn_blocking(move || {
+        let commit = || {
+            opts.game_dir = Some(to.clone());
+            OptionsRepository::save_launcher_options(&opts)
+        };
+        game_dir_mover::move_game_dir(&guard, &from, &to, keep_source, commit, |progress| {
+            events.emit("game-dir-move-progress", progress.clone())
+        })
+    })
+    .await
+    .map_err(|e| format!("Moving the game directory failed: {e}"))?
 }
 
 #[tauri::command]
@@ -51,10 +97,49 @@ pub fn get_base_jvm_flags() -> Vec<String> {
     flags
 }
 
+/// The curated JVM presets offered in the VM settings.
 #[tauri::command]
-pub fn save_game_options(game_options: GameOptions, launcher_options: LauncherOptions) -> bool {
+pub fn get_jvm_presets() -> Vec<JvmPreset> {
+    jvm_flags::PRESETS.to_vec()
+}
+
+/// Category of each configured flag and whether it is used with the chosen collector.
+#[tauri::command]
+pub fn describe_jvm_flags(game_options: GameOptions) -> Vec<FlagInfo> {
+    jvm_flags::describe(game_options.get_flags(), game_options.get_garbage_collector())
+}
+
+/// Validates and saves the game options, like `save_options`.
+#[tauri::command]
+pub fn save_game_options(game_options: GameOptions, launcher_options: LauncherOptions) -> Result<(), OptionsError> {
     info!("Saving game options: {:?}", game_options);
-    OptionsRepository::save_game_options(&game_options, &launcher_options);
+    game_options.validate()?;
+    OptionsRepository::save_game_options(&game_options, &launcher_options)
+        .map_err(|message| OptionsError::Io { message })?;
     info!("Game options saved correctly");
-    true
+    Ok(())
+}
+
+/// Saves the launcher and game options, without the Microsoft token, to a bundle at `path`.
+#[tauri::command]
+pub fn export_settings(path: String) -> Result<(), String> {
+    info!("Exporting settings to {}", path);
+    let options = OptionsRepository::load_launcher_options();
+    settings_bundle::export_settings(&options, std::path::Path::new(&path))
+}
+
+/// Checks a settings bundle and returns what importing it changes; with
+/// `apply`, also saves the imported options.
+#[tauri::command]
+pub fn import_settings(path: String, apply: bool) -> Result<SettingsImport, String> {
+    info!("Importing settings from {} (apply: {})", path, apply);
+    let options = OptionsRepository::load_launcher_options();
+    settings_bundle::import_settings(&options, std::path::Path::new(&path), apply)
+}
+
+/// Problems found while loading the options files (migrations, unknown
+/// versions, unreadable files) since the last call.
+#[tauri::command]
+pub fn take_options_notices() -> Vec<OptionsNotice> {
+    options_migration::take_notices()
 }
@@ -1,14 +1,23 @@
 use chrono::Local;
 use log::{info, error, LevelFilter};
-use simplelog::{WriteLogger, Config, CombinedLogger, TermLogger, TerminalMode, ColorChoice};
+use simplelog::{WriteLogger, Config, CombinedLogger, SharedLogger, TermLogger, TerminalMode, ColorChoice};
 use std::fs::{File, create_dir_all};
 
-use crate::models::options::LauncherOptions;
+use crate::core::paths;
 
 /// Configure the logger to log to both console and a file in the logs directory.
-pub fn setup_logger(options: &LauncherOptions) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(dir) = &options.launcher_dir {
-        let logs_dir = dir.join("logs");
+pub fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
+    init(true)
+}
+
+/// Configure the logger to log only to a file, keeping the terminal free for
+/// the output of headless commands.
+pub fn setup_file_logger() -> Result<(), Box<dyn std::error::Error>> {
+    init(false)
+}
+
+fn init(to_terminal: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(logs_dir) = paths::logs_dir() {
         create_dir_all(&logs_dir)?;
 
         let date_str = Local::now().format("%Y-%m-%d").to_string();
@@ -22,19 +31,22 @@ pub fn setup_logger(options: &LauncherOptions) -> Result<(), Box<dyn std::error:
 
         let log_path = logs_dir.join(&log_filename);
 
-        CombinedLogger::init(vec![
-            TermLogger::new(
-                LevelFilter::Info,
-                Config::default(),
-                TerminalMode::Mixed,
-                ColorChoice::Auto
-            ),
+        let mut loggers: Vec<Box<dyn SharedLogger>> = vec![
             WriteLogger::new(
                 LevelFilter::Info,
                 Config::default(),
                 File::create(log_path)?
             )
-        ])?;
+        ];
+        if to_terminal {
+            loggers.push(TermLogger::new(
+                LevelFilter::Info,
+                Config::default(),
+                TerminalMode::Mixed,
+                ColorChoice::Auto
+            ));
+        }
+        CombinedLogger::init(loggers)?;
 
         info!("Logger inicializado correctamente en: {}", log_filename);
         Ok(())
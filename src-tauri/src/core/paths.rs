@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use log::{info, warn};
+use serde::Serialize;
+
+/// Folder names used under the platform config/data directories, and next to
+/// the executable in portable mode.
+const LAUNCHER_DIR_NAME: &str = ".Permadeath-Launcher";
+const GAME_DIR_NAME: &str = ".Permadeath";
+
+/// A file with this name next to the executable turns on portable mode.
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+/// Kept in the default launcher directory when the data was moved elsewhere;
+/// holds the absolute path of the new launcher directory.
+const POINTER_FILE: &str = "launcher-location.txt";
+
+/// How the launcher directory was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocationMode {
+    /// The platform config directory.
+    Default,
+    /// A folder named by the pointer file in the default directory.
+    Relocated,
+    /// Next to the executable, because of the portable marker.
+    Portable,
+}
+
+/// Where the launcher keeps its data, resolved once per process.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Locations {
+    pub mode: LocationMode,
+    pub launcher_dir: Option<PathBuf>,
+    pub default_game_dir: Option<PathBuf>,
+}
+
+static LOCATIONS: OnceLock<Locations> = OnceLock::new();
+
+/// The resolved locations. A relocation saved while the launcher runs only
+/// takes effect on the next start, so every subsystem keeps using one folder.
+pub fn locations() -> &'static Locations {
+    LOCATIONS.get_or_init(resolve)
+}
+
+pub fn launcher_dir() -> Option<PathBuf> {
+    locations().launcher_dir.clone()
+}
+
+pub fn default_game_dir() -> Option<PathBuf> {
+    locations().default_game_dir.clone()
+}
+
+pub fn is_portable() -> bool {
+    locations().mode == LocationMode::Portable
+}
+
+/// Folder of helper programs such as the game manager JAR.
+pub fn tools_dir() -> Option<PathBuf> {
+    launcher_dir().map(|dir| dir.join("tools"))
+}
+
+pub fn logs_dir() -> Option<PathBuf> {
+    launcher_dir().map(|dir| dir.join("logs"))
+}
+
+fn resolve() -> Locations {
+    if let Some(base) = portable_base() {
+        info!("Portable mode: keeping all data in {:?}", base);
+        return Locations {
+            mode: LocationMode::Portable,
+            launcher_dir: Some(base.join(LAUNCHER_DIR_NAME)),
+            default_game_dir: Some(base.join(GAME_DIR_NAME)),
+        };
+    }
+
+    let default_dir = default_launcher_dir();
+    let default_game_dir = dirs_next::data_dir().map(|dir| dir.join(GAME_DIR_NAME));
+    if let Some(target) = default_dir.as_deref().and_then(read_pointer) {
+        info!("Launcher directory relocated to {:?}", target);
+        return Locations {
+            mode: LocationMode::Relocated,
+            launcher_dir: Some(target),
+            default_game_dir,
+        };
+    }
+
+    Locations {
+        mode: LocationMode::Default,
+        launcher_dir: default_dir,
+        default_game_dir,
+    }
+}
+
+fn default_launcher_dir() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|dir| dir.join(LAUNCHER_DIR_NAME))
+}
+
+/// The executable's folder, when the portable marker is next to it.
+fn portable_base() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let base = exe.parent()?;
+    base.join(PORTABLE_MARKER).is_file().then(|| base.to_path_buf())
+}
+
+/// The folder named by the pointer file. A pointer to a missing folder (an
+/// unplugged drive, say) is ignored rather than filled with new defaults.
+fn read_pointer(default_dir: &Path) -> Option<PathBuf> {
+    let pointer = default_dir.join(POINTER_FILE);
+    let content = fs::read_to_string(&pointer).ok()?;
+    let target = PathBuf::from(content.trim());
+    if !target.is_absolute() {
+        warn!("Ignoring {:?}: {:?} is not an absolute path", pointer, target);
+        return None;
+    }
+    if !target.is_dir() {
+        warn!("Ignoring {:?}: {:?} does not exist", pointer, target);
+        return None;
+    }
+    Some(target)
+}
+
+/// Makes the launcher use `dir` from the next start on, by writing or removing
+/// the pointer in the default directory. Not available in portable mode.
+pub fn relocate(dir: &Path) -> Result<(), String> {
+    if is_portable() {
+        return Err("In portable mode the data stays next to the launcher.".to_string());
+    }
+    let default_dir = default_launcher_dir().ok_or("The default launcher directory is unknown.")?;
+    let pointer = default_dir.join(POINTER_FILE);
+
+    if dir == default_dir {
+        return match fs::remove_file(&pointer) {
+            Ok(_) => {
+                info!("Launcher directory moved back to {:?}", default_dir);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Could not remove {}: {}", pointer.display(), e)),
+        };
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    fs::create_dir_all(&default_dir).map_err(|e| format!("Could not create {}: {}", default_dir.display(), e))?;
+    fs::write(&pointer, dir.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Could not write {}: {}", pointer.display(), e))?;
+    info!("Launcher directory will be {:?} from the next start", dir);
+    Ok(())
+}
+
+/// In portable mode, paths inside the executable's folder are stored relative
+/// to it, so the folder still works after being moved or given a new drive letter.
+pub fn to_stored(path: &Path) -> PathBuf {
+    match portable_root() {
+        Some(root) => path.strip_prefix(&root).map(Path::to_path_buf).unwrap_or_else(|_| path.to_path_buf()),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Turns a stored path back into an absolute one. See `to_stored`.
+pub fn from_stored(path: &Path) -> PathBuf {
+    match portable_root() {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn portable_root() -> Option<PathBuf> {
+    if !is_portable() {
+        return None;
+    }
+    launcher_dir().and_then(|dir| dir.parent().map(Path::to_path_buf))
+}
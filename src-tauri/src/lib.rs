@@ -1,3 +1,4 @@
+pub mod cli;
 mod commands;
 mod core;
 mod database;
@@ -6,11 +7,16 @@ mod services;
 
 use models::options::{LauncherOptions, GameOptions};
 use log::{info, error};
+use tauri::Manager;
 use database::DbManager;
 use services::session_service::SessionService;
 use services::options_repository::OptionsRepository;
 use services::java_installer;
 use services::game_service::GameManager;
+use services::event_sink::EventSink;
+use services::deep_link::{self, PendingDeepLink};
+use services::instance::{self, Instance};
+use tauri_plugin_deep_link::DeepLinkExt;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 #[tokio::main]
@@ -19,20 +25,39 @@ pub async fn run() {
     let game_options = GameOptions::new();
 
     // Logger setup
-    if let Err(e) = core::logger::setup_logger(&options) {
+    if let Err(e) = core::logger::setup_logger() {
         eprintln!("Error while setting up the logger: {}", e);
     }
 
     info!("Starting application");
 
+    // A second launcher would get its own GameManager and could start another
+    // game client in the same directory, so hand the arguments to the first one
+    let instance = match instance::acquire(&options) {
+        Ok(Instance::Primary(guard)) => Some(guard),
+        Ok(Instance::Forwarded(Ok(reply))) => {
+            info!("{}", reply);
+            return;
+        }
+        Ok(Instance::Forwarded(Err(e))) => {
+            error!("The running launcher refused the request: {}", e);
+            java_installer::show_info_message(&e);
+            return;
+        }
+        Err(e) => {
+            error!("Single-instance check skipped: {}", e);
+            None
+        }
+    };
+
     if !OptionsRepository::is_launcher_json_present(&options) {
         info!("Options file not found, creating a new one with default settings");
-        OptionsRepository::save_launcher_options(&options);
+        let _ = OptionsRepository::save_launcher_options(&options);
     }
 
     if !OptionsRepository::is_game_json_present(&options) {
         info!("Game Options file not found, creating a new one with default settings");
-        OptionsRepository::save_game_options(&game_options, &options);
+        let _ = OptionsRepository::save_game_options(&game_options, &options);
     }
 
     // --- DATABASE CONNECTION ---
@@ -66,9 +91,44 @@ pub async fn run() {
             .plugin(tauri_plugin_dialog::init())
             .plugin(tauri_plugin_process::init())
             .plugin(tauri_plugin_opener::init())
+            .plugin(tauri_plugin_clipboard_manager::init())
+            .plugin(tauri_plugin_deep_link::init())
             .manage(db_manager)
             .manage(session_service)
             .manage(GameManager::new())
+            .manage(PendingDeepLink::default())
+            .setup(move |app| {
+                if let Some(guard) = instance {
+                    let handle = app.handle().clone();
+                    tauri::async_runtime::spawn(guard.serve(move |args| cli::forward_to_ui(handle.clone(), args)));
+                }
+
+                // Hand edits and CLI changes to the options files reach the UI
+                services::options_watcher::spawn(app.handle().clone());
+
+                // Pick up a game that outlived the previous launcher process
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let events = EventSink::App(handle.clone());
+                    handle.state::<GameManager>().reattach(events).await;
+                });
+
+                // Linux and Windows only learn about the scheme from the desktop
+                // entry / registry key, which installers do not always write.
+                #[cfg(any(target_os = "linux", windows))]
+                if let Err(e) = app.deep_link().register_all() {
+                    error!("Could not register the {}:// URL scheme: {}", deep_link::SCHEME, e);
+                }
+
+                let handle = app.handle().clone();
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    deep_link::handle_urls(&handle, urls);
+                }
+                app.deep_link().on_open_url(move |event| {
+                    deep_link::handle_urls(&handle, event.urls());
+                });
+                Ok(())
+            })
             .invoke_handler(tauri::generate_handler![
                 commands::options::read_options,
                 commands::options::save_options,
@@ -76,17 +136,33 @@ pub async fn run() {
                 commands::options::read_game_options,
                 commands::options::get_garbage_collectors,
                 commands::options::get_base_jvm_flags,
+                commands::options::get_jvm_presets,
+                commands::options::describe_jvm_flags,
                 commands::options::save_game_options,
+                commands::options::export_settings,
+                commands::options::import_settings,
+                commands::options::take_options_notices,
+                commands::options::move_game_dir,
                 commands::auth::login_user,
                 commands::auth::register_user,
                 commands::auth::check_session,
                 commands::auth::logout,
                 commands::auth::load_user_data,
                 commands::game::launch_game,
+                commands::game::preview_launch,
                 commands::game::stop_game,
                 commands::game::get_game_state,
+                commands::game::get_game_metrics,
+                commands::game::capture_game_diagnostic,
+                commands::game::list_game_sessions,
+                commands::game::search_game_log,
+                commands::game::share_game_log,
                 commands::game::check_ms_auth_state,
                 commands::game::get_server_status,
+                commands::deep_link::take_pending_deep_link,
+                commands::backup::list_backups,
+                commands::backup::verify_backup,
+                commands::backup::restore_backup,
             ])
             .run(tauri::generate_context!())
             .expect("error while running tauri application");
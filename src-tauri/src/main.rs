@@ -1,5 +1,18 @@
 #![cfg_attr(all(not(debug_assertions)), windows_subsystem = "windows")]
 
+use permadeath_launcher_lib::cli::Cli;
+
 fn main() {
-    permadeath_launcher_lib::run();
+    // Release builds use the GUI subsystem, which has no console of its own;
+    // borrow the terminal the command was typed in so subcommands can print.
+    #[cfg(windows)]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+
+    match Cli::from_env().command {
+        Some(command) => std::process::exit(permadeath_launcher_lib::cli::run(command)),
+        None => permadeath_launcher_lib::run(),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,484 @@
+use std::fmt;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::models::options::GarbageCollector;
+use crate::models::validation::FieldError;
+
+/// One JVM option. Stored and shown as the text the JVM expects
+/// (`-XX:+AlwaysPreTouch`), so `game_options.json` stays easy to edit by hand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum JvmFlag {
+    /// `-XX:+Name` or `-XX:-Name`.
+    Switch { name: String, enabled: bool },
+    /// `-XX:Name=value`.
+    Value { name: String, value: String },
+    /// `-Dkey=value`.
+    Property { key: String, value: String },
+    /// Anything else, such as `-Xss4M` or `-Xverify:none`, kept as written.
+    Other(String),
+}
+
+/// What a flag is about, for grouping in the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagCategory {
+    Memory,
+    Gc,
+    Diagnostics,
+    SystemProperty,
+    Other,
+}
+
+/// `-XX` options that need `-XX:+UnlockExperimentalVMOptions` on Java 21.
+const EXPERIMENTAL: [&str; 3] = ["G1NewSizePercent", "G1MaxNewSizePercent", "G1MixedGCLiveThresholdPercent"];
+
+const UNLOCK_EXPERIMENTAL: &str = "UnlockExperimentalVMOptions";
+const UNLOCK_DIAGNOSTIC: &str = "UnlockDiagnosticVMOptions";
+
+const DIAGNOSTIC_OPTIONS: [&str; 6] = [
+    UNLOCK_EXPERIMENTAL,
+    UNLOCK_DIAGNOSTIC,
+    "PerfDisableSharedMem",
+    "HeapDumpOnOutOfMemoryError",
+    "HeapDumpPath",
+    "ErrorFile",
+];
+
+const MEMORY_OPTIONS: [&str; 7] = [
+    "AlwaysPreTouch",
+    "UseLargePages",
+    "UseTransparentHugePages",
+    "MaxMetaspaceSize",
+    "MetaspaceSize",
+    "MaxDirectMemorySize",
+    "ReservedCodeCacheSize",
+];
+
+/// GC tuning that applies to every collector.
+const GENERAL_GC_OPTIONS: [&str; 4] = [
+    "DisableExplicitGC",
+    "ExplicitGCInvokesConcurrent",
+    "ParallelRefProcEnabled",
+    "UseStringDeduplication",
+];
+
+/// Options that contradict each other when both are on.
+const CONFLICTS: [(&str, &str); 2] = [
+    ("DisableExplicitGC", "ExplicitGCInvokesConcurrent"),
+    ("UseLargePages", "UseTransparentHugePages"),
+];
+
+const GENERATIONAL: &[GarbageCollector] = &[GarbageCollector::Serial, GarbageCollector::Parallel, GarbageCollector::G1GC];
+
+impl JvmFlag {
+    pub fn parse(flag: &str) -> Self {
+        let flag = flag.trim();
+        if let Some(rest) = flag.strip_prefix("-XX:") {
+            if let Some(name) = rest.strip_prefix('+') {
+                return JvmFlag::Switch { name: name.to_string(), enabled: true };
+            }
+            if let Some(name) = rest.strip_prefix('-') {
+                return JvmFlag::Switch { name: name.to_string(), enabled: false };
+            }
+            if let Some((name, value)) = rest.split_once('=') {
+                return JvmFlag::Value { name: name.to_string(), value: value.to_string() };
+            }
+        }
+        if let Some((key, value)) = flag.strip_prefix("-D").and_then(|rest| rest.split_once('=')) {
+            return JvmFlag::Property { key: key.to_string(), value: value.to_string() };
+        }
+        JvmFlag::Other(flag.to_string())
+    }
+
+    /// The setting a flag controls, so `-XX:+Foo` / `-XX:-Foo` and
+    /// `-Xss1M` / `-Xss4M` count as the same one: `Foo`, `-Dkey`, `-Xss`.
+    pub fn name(&self) -> String {
+        match self {
+            JvmFlag::Switch { name, .. } | JvmFlag::Value { name, .. } => name.clone(),
+            JvmFlag::Property { key, .. } => format!("-D{}", key),
+            JvmFlag::Other(flag) => {
+                if ["-Xss", "-Xmx", "-Xms", "-Xmn"].iter().any(|p| flag.starts_with(p)) {
+                    flag[..4].to_string()
+                } else {
+                    flag.split([':', '=']).next().unwrap_or(flag).to_string()
+                }
+            }
+        }
+    }
+
+    /// The `-XX` option name, for switches and values.
+    fn option(&self) -> Option<&str> {
+        match self {
+            JvmFlag::Switch { name, .. } | JvmFlag::Value { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    fn is_on(&self, option: &str) -> bool {
+        matches!(self, JvmFlag::Switch { name, enabled: true } if name == option)
+    }
+
+    /// `-XX:+UseG1GC` and friends; the collector comes from `garbage_collector`.
+    pub fn is_gc_selector(&self) -> bool {
+        matches!(self, JvmFlag::Switch { name, .. } if name.starts_with("Use") && name.ends_with("GC"))
+    }
+
+    pub fn is_heap_size(&self) -> bool {
+        matches!(self, JvmFlag::Other(flag) if flag.starts_with("-Xmx") || flag.starts_with("-Xms"))
+    }
+
+    fn is_unlock(&self) -> bool {
+        self.is_on(UNLOCK_EXPERIMENTAL) || self.is_on(UNLOCK_DIAGNOSTIC)
+    }
+
+    /// The collectors a flag means something to, or `None` for all of them.
+    pub fn collectors(&self) -> Option<&'static [GarbageCollector]> {
+        let option = self.option()?;
+        if option.starts_with("G1") || option == "InitiatingHeapOccupancyPercent" {
+            Some(&[GarbageCollector::G1GC])
+        } else if option.starts_with('Z') && option[1..].starts_with(char::is_uppercase) {
+            Some(&[GarbageCollector::ZGC])
+        } else if option.starts_with("Shenandoah") {
+            Some(&[GarbageCollector::Shenandoah])
+        } else if option == "MaxGCPauseMillis" {
+            Some(&[GarbageCollector::Parallel, GarbageCollector::G1GC])
+        } else if ["SurvivorRatio", "MaxTenuringThreshold", "NewRatio"].contains(&option) {
+            Some(GENERATIONAL)
+        } else {
+            None
+        }
+    }
+
+    pub fn applies_to(&self, gc: &GarbageCollector) -> bool {
+        self.collectors().is_none_or(|collectors| collectors.contains(gc))
+    }
+
+    pub fn category(&self) -> FlagCategory {
+        let Some(option) = self.option() else {
+            return match self {
+                JvmFlag::Property { .. } => FlagCategory::SystemProperty,
+                JvmFlag::Other(flag) if flag.starts_with("-Xs") || flag.starts_with("-Xm") => FlagCategory::Memory,
+                JvmFlag::Other(flag) if flag.starts_with("-Xlog") || flag.starts_with("-verbose") => {
+                    FlagCategory::Diagnostics
+                }
+                _ => FlagCategory::Other,
+            };
+        };
+        if DIAGNOSTIC_OPTIONS.contains(&option) || option.starts_with("Print") {
+            FlagCategory::Diagnostics
+        } else if self.collectors().is_some() || GENERAL_GC_OPTIONS.contains(&option) {
+            FlagCategory::Gc
+        } else if MEMORY_OPTIONS.contains(&option) {
+            FlagCategory::Memory
+        } else {
+            FlagCategory::Other
+        }
+    }
+}
+
+impl fmt::Display for JvmFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JvmFlag::Switch { name, enabled } => write!(f, "-XX:{}{}", if *enabled { '+' } else { '-' }, name),
+            JvmFlag::Value { name, value } => write!(f, "-XX:{}={}", name, value),
+            JvmFlag::Property { key, value } => write!(f, "-D{}={}", key, value),
+            JvmFlag::Other(flag) => f.write_str(flag),
+        }
+    }
+}
+
+impl From<String> for JvmFlag {
+    fn from(flag: String) -> Self {
+        JvmFlag::parse(&flag)
+    }
+}
+
+impl From<JvmFlag> for String {
+    fn from(flag: JvmFlag) -> Self {
+        flag.to_string()
+    }
+}
+
+/// How one of the configured flags is treated, for the VM settings page.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagInfo {
+    pub flag: String,
+    pub category: FlagCategory,
+    /// `false` when the flag is left out for the selected collector.
+    pub active: bool,
+}
+
+pub fn describe(flags: &[JvmFlag], gc: &GarbageCollector) -> Vec<FlagInfo> {
+    flags
+        .iter()
+        .map(|flag| FlagInfo {
+            flag: flag.to_string(),
+            category: flag.category(),
+            active: flag.applies_to(gc),
+        })
+        .collect()
+}
+
+/// The JVM arguments for `flags` under `gc`: unlock switches first so the
+/// options after them are accepted, flags meant for other collectors left
+/// out, and the collector selected last.
+pub fn command_line(flags: &[JvmFlag], gc: &GarbageCollector) -> Vec<String> {
+    let (unlocks, rest): (Vec<&JvmFlag>, Vec<&JvmFlag>) =
+        flags.iter().filter(|f| !f.is_gc_selector()).partition(|f| f.is_unlock());
+
+    let mut args: Vec<String> = unlocks.iter().map(|f| f.to_string()).collect();
+    for flag in rest {
+        if flag.applies_to(gc) {
+            args.push(flag.to_string());
+        } else {
+            info!("Leaving out {}, which does not apply to {:?}", flag, gc);
+        }
+    }
+    args.push(gc.flag().to_string());
+    args
+}
+
+/// Problems with a flag list, keyed `vm_flags[i]`: non-options, settings
+/// that have their own field, duplicates, missing unlocks and conflicts.
+pub fn validate(flags: &[JvmFlag]) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    let unlocked = flags.iter().any(|f| f.is_on(UNLOCK_EXPERIMENTAL));
+
+    for (i, flag) in flags.iter().enumerate() {
+        let field = format!("vm_flags[{}]", i);
+        let text = flag.to_string();
+        if !text.starts_with('-') {
+            errors.push(FieldError::new(field, format!("`{}` is not a JVM option; options start with `-`.", text)));
+            continue;
+        }
+        if flag.is_gc_selector() {
+            errors.push(FieldError::new(
+                field,
+                format!("`{}` selects a garbage collector; choose it with the Garbage Collector setting.", text),
+            ));
+            continue;
+        }
+        if flag.is_heap_size() {
+            errors.push(FieldError::new(
+                field,
+                format!("`{}` sets the heap size; use the Allocated Memory setting.", text),
+            ));
+            continue;
+        }
+
+        let name = flag.name();
+        if seen.contains(&name) {
+            errors.push(FieldError::new(field, format!("`{}` is set more than once.", name)));
+            continue;
+        }
+        seen.push(name);
+
+        if !unlocked && flag.option().is_some_and(|o| EXPERIMENTAL.contains(&o)) {
+            errors.push(FieldError::new(
+                field.clone(),
+                format!("`{}` needs `-XX:+{}` before it.", text, UNLOCK_EXPERIMENTAL),
+            ));
+        }
+        for (a, b) in CONFLICTS {
+            if flag.is_on(b) && flags.iter().any(|f| f.is_on(a)) {
+                errors.push(FieldError::new(field.clone(), format!("`-XX:+{}` cannot be used with `-XX:+{}`.", b, a)));
+            }
+        }
+    }
+
+    let percent = |option: &str| {
+        flags.iter().find_map(|f| match f {
+            JvmFlag::Value { name, value } if name == option => value.parse::<u32>().ok(),
+            _ => None,
+        })
+    };
+    if let (Some(min), Some(max)) = (percent("G1NewSizePercent"), percent("G1MaxNewSizePercent")) {
+        if min > max {
+            let index = flags.iter().position(|f| f.option() == Some("G1NewSizePercent")).unwrap_or(0);
+            errors.push(FieldError::new(
+                format!("vm_flags[{}]", index),
+                format!("G1NewSizePercent ({}) is larger than G1MaxNewSizePercent ({}).", min, max),
+            ));
+        }
+    }
+    errors
+}
+
+// ---------------------------------------------------------------------------
+// Presets
+// ---------------------------------------------------------------------------
+
+/// A curated set of JVM options, applied from the VM settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct JvmPreset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub garbage_collector: GarbageCollector,
+    /// Memory the preset is tuned for, when it matters.
+    pub max_ram: Option<u32>,
+    pub flags: &'static [&'static str],
+}
+
+pub const PRESETS: [JvmPreset; 4] = [
+    JvmPreset {
+        id: "permadeath",
+        name: "PERMADEATHSMP default",
+        description: "The launcher's G1 settings for the modpack.",
+        garbage_collector: GarbageCollector::G1GC,
+        max_ram: None,
+        flags: &crate::models::options::BASE_VM_FLAGS,
+    },
+    // From https://docs.papermc.io/paper/aikars-flags, without
+    // G1RSetUpdatingPauseTimePercent, which Java 21 no longer has
+    JvmPreset {
+        id: "aikar_g1",
+        name: "Aikar's G1",
+        description: "Well-known G1 tuning with short pauses. Best with 6 GB or more.",
+        garbage_collector: GarbageCollector::G1GC,
+        max_ram: None,
+        flags: &[
+            "-XX:+UnlockExperimentalVMOptions",
+            "-XX:+ParallelRefProcEnabled",
+            "-XX:MaxGCPauseMillis=200",
+            "-XX:+DisableExplicitGC",
+            "-XX:+AlwaysPreTouch",
+            "-XX:G1NewSizePercent=30",
+            "-XX:G1MaxNewSizePercent=40",
+            "-XX:G1HeapRegionSize=8M",
+            "-XX:G1ReservePercent=20",
+            "-XX:G1HeapWastePercent=5",
+            "-XX:G1MixedGCCountTarget=4",
+            "-XX:InitiatingHeapOccupancyPercent=15",
+            "-XX:G1MixedGCLiveThresholdPercent=90",
+            "-XX:SurvivorRatio=32",
+            "-XX:+PerfDisableSharedMem",
+            "-XX:MaxTenuringThreshold=1",
+            "-Dusing.aikars.flags=https://mcflags.emc.gs",
+            "-Daikars.new.flags=true",
+        ],
+    },
+    JvmPreset {
+        id: "zgc_generational",
+        name: "ZGC generational",
+        description: "Near pause-free collection on Java 21. Needs spare memory and CPU cores.",
+        garbage_collector: GarbageCollector::ZGC,
+        max_ram: None,
+        flags: &[
+            "-XX:+ZGenerational",
+            "-XX:+AlwaysPreTouch",
+            "-XX:+DisableExplicitGC",
+            "-XX:+PerfDisableSharedMem",
+            "-XX:+UseStringDeduplication",
+        ],
+    },
+    JvmPreset {
+        id: "low_memory",
+        name: "Low memory",
+        description: "For computers with 8 GB of RAM or less. Smaller heap regions and no pre-touching.",
+        garbage_collector: GarbageCollector::G1GC,
+        max_ram: Some(3072),
+        flags: &[
+            "-XX:MaxGCPauseMillis=200",
+            "-XX:G1HeapRegionSize=4M",
+            "-XX:G1ReservePercent=15",
+            "-XX:+UseStringDeduplication",
+            "-XX:+DisableExplicitGC",
+            "-XX:ReservedCodeCacheSize=128M",
+        ],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(list: &[&str]) -> Vec<JvmFlag> {
+        list.iter().map(|f| JvmFlag::parse(f)).collect()
+    }
+
+    #[test]
+    fn parses_and_prints_every_kind() {
+        for text in ["-XX:+AlwaysPreTouch", "-XX:-UseBiasedLocking", "-XX:G1HeapRegionSize=32M", "-Dfile.encoding=UTF-8", "-Xss4M"] {
+            assert_eq!(JvmFlag::parse(text).to_string(), text);
+        }
+        assert_eq!(
+            JvmFlag::parse("-XX:MaxGCPauseMillis=50"),
+            JvmFlag::Value { name: "MaxGCPauseMillis".into(), value: "50".into() }
+        );
+    }
+
+    #[test]
+    fn g1_flags_are_dropped_for_zgc() {
+        let list = flags(&["-XX:G1NewSizePercent=30", "-XX:G1HeapRegionSize=32M", "-XX:+AlwaysPreTouch", "-XX:+ZGenerational"]);
+        assert_eq!(
+            command_line(&list, &GarbageCollector::ZGC),
+            vec!["-XX:+AlwaysPreTouch", "-XX:+ZGenerational", "-XX:+UseZGC"]
+        );
+        assert_eq!(
+            command_line(&list, &GarbageCollector::G1GC),
+            vec!["-XX:G1NewSizePercent=30", "-XX:G1HeapRegionSize=32M", "-XX:+AlwaysPreTouch", "-XX:+UseG1GC"]
+        );
+    }
+
+    #[test]
+    fn unlock_flags_come_first() {
+        let list = flags(&["-XX:G1NewSizePercent=30", "-XX:+UnlockExperimentalVMOptions", "-XX:+AlwaysPreTouch"]);
+        let args = command_line(&list, &GarbageCollector::G1GC);
+        assert_eq!(args[0], "-XX:+UnlockExperimentalVMOptions");
+        assert_eq!(args.last().map(String::as_str), Some("-XX:+UseG1GC"));
+    }
+
+    #[test]
+    fn gc_selector_is_rejected_and_never_emitted() {
+        let list = flags(&["-XX:+AlwaysPreTouch", "-XX:+UseParallelGC"]);
+        let errors = validate(&list);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "vm_flags[1]");
+        assert!(!command_line(&list, &GarbageCollector::G1GC).contains(&"-XX:+UseParallelGC".to_string()));
+    }
+
+    #[test]
+    fn heap_size_is_rejected() {
+        let errors = validate(&flags(&["-Xmx4G"]));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "vm_flags[0]");
+    }
+
+    #[test]
+    fn duplicates_are_rejected() {
+        let errors = validate(&flags(&["-XX:MaxGCPauseMillis=100", "-XX:+AlwaysPreTouch", "-XX:MaxGCPauseMillis=50"]));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "vm_flags[2]");
+    }
+
+    #[test]
+    fn experimental_flag_needs_unlock() {
+        let errors = validate(&flags(&["-XX:G1NewSizePercent=30"]));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains(UNLOCK_EXPERIMENTAL));
+
+        assert!(validate(&flags(&["-XX:G1NewSizePercent=30", "-XX:+UnlockExperimentalVMOptions"])).is_empty());
+    }
+
+    #[test]
+    fn conflicts_are_rejected() {
+        let errors = validate(&flags(&["-XX:+DisableExplicitGC", "-XX:+ExplicitGCInvokesConcurrent"]));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "vm_flags[1]");
+
+        // Turned off, the second one does not conflict
+        assert!(validate(&flags(&["-XX:+DisableExplicitGC", "-XX:-ExplicitGCInvokesConcurrent"])).is_empty());
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for preset in &PRESETS {
+            let list = flags(preset.flags);
+            assert!(validate(&list).is_empty(), "preset {} has problems: {:?}", preset.id, validate(&list));
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod options;
+pub mod validation;
+pub mod jvm_flags;
 pub mod user;
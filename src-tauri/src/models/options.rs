@@ -1,7 +1,10 @@
-use std::path::PathBuf;
-use dirs_next::config_dir;
+use std::path::{Component, Path, PathBuf};
 use log::info;
 
+use crate::core::paths;
+use crate::models::jvm_flags::{self, JvmFlag};
+use crate::models::validation::FieldError;
+
 pub const BASE_VM_FLAGS: [&str; 10] = [
     "-XX:+UnlockExperimentalVMOptions", // Must be first — required before any experimental flags
     "-XX:MaxGCPauseMillis=100",
@@ -15,7 +18,30 @@ pub const BASE_VM_FLAGS: [&str; 10] = [
     "-Xverify:none",
 ];
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// Layout version of `options.json`. Bump it and add a step to
+/// `options_migration` whenever a change needs more than `#[serde(default)]`.
+pub const LAUNCHER_SCHEMA_VERSION: u32 = 1;
+/// Layout version of `game_options.json`. Version 2 moved the collector and
+/// heap size out of `vm_flags`.
+pub const GAME_SCHEMA_VERSION: u32 = 2;
+
+/// Bounds of `max_ram`, in MB. The modpack does not start with less.
+pub const MIN_RAM_MB: u32 = 1024;
+pub const MAX_RAM_MB: u32 = 65536;
+
+/// Longest accepted hook timeout and metrics interval, in seconds.
+const MAX_HOOK_TIMEOUT_SECS: u64 = 3600;
+const MAX_METRICS_INTERVAL_SECS: u64 = 3600;
+
+fn launcher_schema_version() -> u32 {
+    LAUNCHER_SCHEMA_VERSION
+}
+
+fn game_schema_version() -> u32 {
+    GAME_SCHEMA_VERSION
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum GarbageCollector {
     Serial,
     Parallel,
@@ -24,36 +50,167 @@ pub enum GarbageCollector {
     Shenandoah,
 }
 
+impl GarbageCollector {
+    /// The JVM option that selects this collector.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            GarbageCollector::Serial => "-XX:+UseSerialGC",
+            GarbageCollector::Parallel => "-XX:+UseParallelGC",
+            GarbageCollector::G1GC => "-XX:+UseG1GC",
+            GarbageCollector::ZGC => "-XX:+UseZGC",
+            GarbageCollector::Shenandoah => "-XX:+UseShenandoahGC",
+        }
+    }
+}
+
+fn default_hook_timeout() -> u64 {
+    60
+}
+
+/// A user-defined program run by the launcher at a given point of a game session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HookCommand {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds the hook may run before it is killed.
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+/// Hooks run by `GameManager` around each game session. Unset hooks are skipped.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct LaunchHooks {
+    /// Runs before the game process is spawned.
+    pub pre_launch: Option<HookCommand>,
+    /// Runs after the game exits, whatever the exit code.
+    pub post_exit: Option<HookCommand>,
+    /// Runs after the game exits with a non-zero code without being stopped by the user.
+    pub on_crash: Option<HookCommand>,
+}
+
+fn default_metrics_interval() -> u64 {
+    5
+}
+
+fn default_backups_kept() -> usize {
+    10
+}
+
+/// Which automatic backups to delete after a new one is taken.
+/// The newest backup is always kept.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BackupRetention {
+    /// Number of backups to keep.
+    #[serde(default = "default_backups_kept")]
+    pub keep_last: usize,
+    /// Delete backups older than this many days.
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Delete the oldest backups while all of them take more than this many MB.
+    #[serde(default)]
+    pub max_total_mb: Option<u64>,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        Self {
+            keep_last: default_backups_kept(),
+            max_age_days: None,
+            max_total_mb: None,
+        }
+    }
+}
+
+fn default_periodic_interval() -> u32 {
+    30
+}
+
+fn default_periodic_folders() -> Vec<String> {
+    vec!["saves".to_string()]
+}
+
+fn default_backup_io_limit() -> Option<u32> {
+    Some(20)
+}
+
+/// Backups taken on a timer while the game runs.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PeriodicBackup {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between two backups.
+    #[serde(default = "default_periodic_interval")]
+    pub interval_minutes: u32,
+    /// Folders and files to back up, relative to `game_dir`.
+    #[serde(default = "default_periodic_folders")]
+    pub folders: Vec<String>,
+    /// Read rate limit in MB/s, so the backup doesn't make the game stutter.
+    /// `None` reads as fast as the disk allows.
+    #[serde(default = "default_backup_io_limit")]
+    pub max_io_mb_per_sec: Option<u32>,
+}
+
+impl Default for PeriodicBackup {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_periodic_interval(),
+            folders: default_periodic_folders(),
+            max_io_mb_per_sec: default_backup_io_limit(),
+        }
+    }
+}
+
+/// How automatic backups are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum BackupFormat {
+    /// Snapshots in a deduplicated store: unchanged data is stored only once.
+    #[default]
+    Incremental,
+    /// A self-contained zip archive per backup.
+    Zip,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LauncherOptions {
+    #[serde(default = "launcher_schema_version")]
+    pub schema_version: u32,
     pub launcher_dir: Option<PathBuf>,
     pub game_dir: Option<PathBuf>,
     pub init_on_start: bool,
     pub auto_update: bool,
     pub notification_enabled: bool,
     pub debug_console: bool,
-    automatic_backup: bool,
+    /// Back up worlds and client settings before each launch and after each exit.
+    pub automatic_backup: bool,
+    #[serde(default)]
+    pub backup_format: BackupFormat,
+    #[serde(default)]
+    pub backup_retention: BackupRetention,
+    #[serde(default)]
+    pub periodic_backup: PeriodicBackup,
     /// Hide the launcher window when the game starts; restore it on exit.
     #[serde(default)]
     pub close_on_launch: bool,
     pub microsoft_token: Option<String>,
+    #[serde(default)]
+    pub hooks: LaunchHooks,
+    /// Seconds between `game-metrics` samples while the game runs; 0 disables sampling.
+    #[serde(default = "default_metrics_interval")]
+    pub metrics_interval_secs: u64,
 }
 
 impl LauncherOptions {
     pub fn new() -> Self {
-        let launcher_dir = config_dir().map(|mut path| {
-            path.push(".Permadeath-Launcher");
-            info!("Launcher directory configured at: {:?}", path);
-            path
-        });
-
-        let game_dir = dirs_next::data_dir().map(|mut path| {
-            path.push(".Permadeath");
-            info!("Game directory configured at: {:?}", path);
-            path
-        });
+        let launcher_dir = paths::launcher_dir();
+        info!("Launcher directory configured at: {:?}", launcher_dir);
+
+        let game_dir = paths::default_game_dir();
+        info!("Game directory configured at: {:?}", game_dir);
 
         Self {
+            schema_version: LAUNCHER_SCHEMA_VERSION,
             launcher_dir,
             game_dir,
             init_on_start: false,
@@ -61,17 +218,128 @@ impl LauncherOptions {
             notification_enabled: false,
             debug_console: false,
             automatic_backup: true,
+            backup_format: BackupFormat::default(),
+            backup_retention: BackupRetention::default(),
+            periodic_backup: PeriodicBackup::default(),
             close_on_launch: false,
             microsoft_token: None,
+            hooks: LaunchHooks::default(),
+            metrics_interval_secs: default_metrics_interval(),
         }
     }
 
     pub fn get_default_game_dir() -> Option<PathBuf> {
-        dirs_next::data_dir().map(|mut path| {
-            path.push(".Permadeath");
-            path
-        })
+        paths::default_game_dir()
     }
+
+    /// Checks every value before the options are saved.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        match &self.launcher_dir {
+            None => errors.push(FieldError::new("launcher_dir", "The launcher directory is not set.")),
+            Some(dir) if !dir.is_absolute() => {
+                errors.push(FieldError::new("launcher_dir", "The launcher directory must be an absolute path."))
+            }
+            Some(dir) if paths::is_portable() && Some(dir) != paths::launcher_dir().as_ref() => {
+                errors.push(FieldError::new("launcher_dir", "In portable mode the data stays next to the launcher."))
+            }
+            Some(_) => {}
+        }
+
+        if let Some(dir) = &self.game_dir {
+            // The default folder is created on the first launch
+            let is_default = Self::get_default_game_dir().as_ref() == Some(dir);
+            if !dir.is_absolute() {
+                errors.push(FieldError::new("game_dir", "The game directory must be an absolute path."));
+            } else if dir.is_file() {
+                errors.push(FieldError::new("game_dir", "The game directory is a file, not a folder."));
+            } else if !dir.exists() && !is_default {
+                errors.push(FieldError::new("game_dir", "The game directory does not exist."));
+            }
+        }
+
+        if self.metrics_interval_secs > MAX_METRICS_INTERVAL_SECS {
+            errors.push(FieldError::new(
+                "metrics_interval_secs",
+                format!("Use at most {} seconds, or 0 to turn metrics off.", MAX_METRICS_INTERVAL_SECS),
+            ));
+        }
+
+        let hooks = [
+            ("pre_launch", &self.hooks.pre_launch),
+            ("post_exit", &self.hooks.post_exit),
+            ("on_crash", &self.hooks.on_crash),
+        ];
+        for (name, hook) in hooks {
+            let Some(hook) = hook else { continue };
+            if hook.command.trim().is_empty() {
+                errors.push(FieldError::new(format!("hooks.{}.command", name), "Enter the program to run."));
+            }
+            if hook.timeout_secs == 0 || hook.timeout_secs > MAX_HOOK_TIMEOUT_SECS {
+                errors.push(FieldError::new(
+                    format!("hooks.{}.timeout_secs", name),
+                    format!("The timeout must be between 1 and {} seconds.", MAX_HOOK_TIMEOUT_SECS),
+                ));
+            }
+        }
+
+        let retention = &self.backup_retention;
+        if retention.keep_last == 0 {
+            errors.push(FieldError::new("backup_retention.keep_last", "Keep at least one backup."));
+        }
+        if retention.max_age_days == Some(0) {
+            errors.push(FieldError::new("backup_retention.max_age_days", "The age limit must be at least one day."));
+        }
+        if retention.max_total_mb == Some(0) {
+            errors.push(FieldError::new("backup_retention.max_total_mb", "The size limit must be at least 1 MB."));
+        }
+
+        let periodic = &self.periodic_backup;
+        if periodic.interval_minutes == 0 {
+            errors.push(FieldError::new("periodic_backup.interval_minutes", "The interval must be at least one minute."));
+        }
+        if periodic.enabled && periodic.folders.is_empty() {
+            errors.push(FieldError::new("periodic_backup.folders", "Choose at least one folder to back up."));
+        }
+        for (i, folder) in periodic.folders.iter().enumerate() {
+            let inside = !folder.is_empty() && Path::new(folder).components().all(|c| matches!(c, Component::Normal(_)));
+            if !inside {
+                errors.push(FieldError::new(
+                    format!("periodic_backup.folders[{}]", i),
+                    "Use a path inside the game directory, such as `saves`.",
+                ));
+            }
+        }
+        if periodic.max_io_mb_per_sec == Some(0) {
+            errors.push(FieldError::new(
+                "periodic_backup.max_io_mb_per_sec",
+                "The rate limit must be at least 1 MB/s; leave it empty for no limit.",
+            ));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// I/O scheduling class applied with `ioprio_set` (see `ionice(1)`).
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum IoniceClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/// Scheduling settings applied to the game process tree. Only honoured on Linux.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProcessPriority {
+    /// Niceness from -20 (highest priority) to 19 (lowest).
+    pub niceness: Option<i32>,
+    pub ionice_class: Option<IoniceClass>,
+    /// Priority inside the I/O class, 0 (highest) to 7. Ignored for `Idle`.
+    pub ionice_level: Option<u8>,
+    /// CPUs the game may run on. `None` leaves the affinity untouched.
+    pub cpu_affinity: Option<Vec<usize>>,
 }
 
 fn default_java_version() -> String {
@@ -80,20 +348,26 @@ fn default_java_version() -> String {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GameOptions {
+    #[serde(default = "game_schema_version")]
+    pub schema_version: u32,
     max_ram: u32,
-    vm_flags: Vec<String>,
+    vm_flags: Vec<JvmFlag>,
     garbage_collector: GarbageCollector,
     #[serde(default = "default_java_version")]
     pub custom_java_path: String,
+    #[serde(default)]
+    process_priority: ProcessPriority,
 }
 
 impl GameOptions {
     pub fn new() -> Self {
         Self {
+            schema_version: GAME_SCHEMA_VERSION,
             max_ram: 4096,
-            vm_flags: BASE_VM_FLAGS.iter().map(|s| s.to_string()).collect(),
+            vm_flags: BASE_VM_FLAGS.iter().map(|s| JvmFlag::parse(s)).collect(),
             garbage_collector: GarbageCollector::G1GC,
             custom_java_path: default_java_version(),
+            process_priority: ProcessPriority::default(),
         }
     }
 
@@ -101,17 +375,21 @@ impl GameOptions {
         self.max_ram
     }
 
+    /// The JVM arguments to launch with, for the selected collector only.
     pub fn get_vm_flags(&self) -> Vec<String> {
-        let mut flags = self.vm_flags.clone();
-        let gc_flag = match self.garbage_collector {
-            GarbageCollector::Serial => "-XX:+UseSerialGC",
-            GarbageCollector::Parallel => "-XX:+UseParallelGC",
-            GarbageCollector::G1GC => "-XX:+UseG1GC",
-            GarbageCollector::ZGC => "-XX:+UseZGC",
-            GarbageCollector::Shenandoah => "-XX:+UseShenandoahGC",
-        };
-        flags.push(gc_flag.into());
-        flags
+        jvm_flags::command_line(&self.vm_flags, &self.garbage_collector)
+    }
+
+    pub fn get_garbage_collector(&self) -> &GarbageCollector {
+        &self.garbage_collector
+    }
+
+    pub fn get_flags(&self) -> &[JvmFlag] {
+        &self.vm_flags
+    }
+
+    pub fn get_process_priority(&self) -> &ProcessPriority {
+        &self.process_priority
     }
 
     pub fn set_max_ram(&mut self, ram_mb: u32) {
@@ -119,19 +397,59 @@ impl GameOptions {
     }
 
     pub fn add_vm_flag(&mut self, flag: String) {
+        let flag = JvmFlag::parse(&flag);
         if !self.vm_flags.contains(&flag) {
             self.vm_flags.push(flag);
         }
     }
 
     pub fn remove_vm_flag(&mut self, flag: &str) {
-        self.vm_flags.retain(|f| f != flag);
+        self.vm_flags.retain(|f| f.to_string() != flag);
     }
 
     pub fn set_garbage_collector(&mut self, gc: GarbageCollector) {
         self.garbage_collector = gc;
     }
 
+    /// Checks every value before the options are saved.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if !(MIN_RAM_MB..=MAX_RAM_MB).contains(&self.max_ram) {
+            errors.push(FieldError::new(
+                "max_ram",
+                format!("Allocate between {} and {} MB.", MIN_RAM_MB, MAX_RAM_MB),
+            ));
+        }
+
+        if self.custom_java_path.trim().is_empty() {
+            errors.push(FieldError::new("custom_java_path", "Choose a Java version."));
+        }
+
+        errors.extend(jvm_flags::validate(&self.vm_flags));
+
+        let priority = &self.process_priority;
+        if priority.niceness.is_some_and(|n| !(-20..=19).contains(&n)) {
+            errors.push(FieldError::new("process_priority.niceness", "Niceness goes from -20 to 19."));
+        }
+        if priority.ionice_level.is_some_and(|l| l > 7) {
+            errors.push(FieldError::new("process_priority.ionice_level", "The I/O priority goes from 0 to 7."));
+        }
+        if let Some(cpus) = &priority.cpu_affinity {
+            let available = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(usize::MAX);
+            if cpus.is_empty() {
+                errors.push(FieldError::new("process_priority.cpu_affinity", "Choose at least one CPU."));
+            } else if let Some(cpu) = cpus.iter().find(|c| **c >= available) {
+                errors.push(FieldError::new(
+                    "process_priority.cpu_affinity",
+                    format!("CPU {} does not exist; this computer has {}.", cpu, available),
+                ));
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
     pub fn get_garbage_collectors() -> Vec<GarbageCollector> {
         vec![
             GarbageCollector::Serial,
@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+/// A problem with one option, keyed by its path in the options JSON
+/// (`max_ram`, `hooks.pre_launch.command`, `vm_flags[2]`), so the UI can show
+/// it next to the matching input.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+/// Why options were not saved.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OptionsError {
+    /// Some values were rejected; nothing was written.
+    Invalid { errors: Vec<FieldError> },
+    /// The values were fine but the file could not be written.
+    Io { message: String },
+}
+
+impl From<Vec<FieldError>> for OptionsError {
+    fn from(errors: Vec<FieldError>) -> Self {
+        OptionsError::Invalid { errors }
+    }
+}
@@ -1,4 +1,7 @@
-use log::error;
+use log::{info, error};
+
+use crate::database::DbManager;
+use crate::services::session_service::SessionService;
 
 /// Validates login input fields
 pub fn validate_login_input(username: &str, password: &str) -> Result<(), String> {
@@ -68,3 +71,44 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
             "Invalid credentials.".to_string()
         })
 }
+
+/// Checks the credentials against the database and opens a new session.
+/// Returns the session token.
+pub async fn login(
+    username: &str,
+    password: &str,
+    db: &DbManager,
+    session_service: &SessionService,
+) -> Result<String, String> {
+    validate_login_input(username, password)?;
+
+    info!("Attempting to authenticate user: {}", username);
+
+    match db.get_user_by_username(username).await {
+        Ok(Some(user)) => {
+            if verify_password(password, &user.password_hash)? {
+                match session_service.create_session(user.id).await {
+                    Ok(token) => {
+                        info!("Successful login for '{}', token created", username);
+                        Ok(token)
+                    }
+                    Err(e) => {
+                        error!("Error creating session for '{}': {}", username, e);
+                        Err("Error creating session.".to_string())
+                    }
+                }
+            } else {
+                info!("Incorrect password for user '{}'", username);
+                Err("Invalid credentials.".to_string())
+            }
+        },
+        Ok(None) => {
+            info!("User '{}' not found.", username);
+            Err("Invalid credentials.".to_string())
+        },
+        Err(e) => {
+            error!("Database error during login: {}", e);
+            Err("Invalid credentials.".to_string())
+        }
+    }
+}
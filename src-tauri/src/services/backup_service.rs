@@ -0,0 +1,1168 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::models::options::{BackupFormat, LauncherOptions};
+use crate::services::backup_store::BackupStore;
+
+/// What gets backed up, relative to `game_dir`.
+pub const BACKUP_SOURCES: [&str; 3] = ["saves", "config", "options.txt"];
+
+/// Minecraft keeps this open while a world is loaded, and it holds no data.
+const SKIPPED_FILES: [&str; 1] = ["session.lock"];
+
+/// Entry written last in every archive, describing and hashing the others.
+const MANIFEST_ENTRY: &str = "backup-manifest.json";
+pub(crate) const MANIFEST_VERSION: u32 = 1;
+
+/// Staging folder inside `game_dir` for restores, so files can be moved into
+/// place with a rename once the whole archive was extracted.
+const RESTORE_STAGING_DIR: &str = ".restore-staging";
+
+/// Times a file is read before giving up on it when it keeps changing
+/// while being copied (the game saving a region file, for instance).
+const READ_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Minimum time between two `backup-progress` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Held while a backup is written or old ones are pruned, so a periodic backup
+/// and the post-exit one never run at once and GC never sees a half-written snapshot.
+static BACKUP_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set for the whole of a restore, from the safety backup to the last swapped
+/// folder. Launches and game directory moves refuse to start while it is set.
+static RESTORING: AtomicBool = AtomicBool::new(false);
+
+/// Whether a backup is being restored into the game directory.
+pub fn restore_in_progress() -> bool {
+    RESTORING.load(Ordering::SeqCst)
+}
+
+/// Proof that this caller is the only restore running. Clears `RESTORING`
+/// when dropped.
+pub struct RestoreGuard(());
+
+impl Drop for RestoreGuard {
+    fn drop(&mut self) {
+        RESTORING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Marks a restore as started. Call it under `GameManager::while_idle` so a
+/// launch cannot begin at the same time.
+pub fn begin_restore() -> Result<RestoreGuard, String> {
+    if RESTORING.swap(true, Ordering::SeqCst) {
+        return Err("A backup is already being restored.".to_string());
+    }
+    Ok(RestoreGuard(()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupTrigger {
+    PreLaunch,
+    PostExit,
+    /// Taken right before a restore overwrites the current files.
+    PreRestore,
+    /// Taken on a timer while the game runs.
+    Periodic,
+}
+
+impl BackupTrigger {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BackupTrigger::PreLaunch => "pre_launch",
+            BackupTrigger::PostExit => "post_exit",
+            BackupTrigger::PreRestore => "pre_restore",
+            BackupTrigger::Periodic => "periodic",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [BackupTrigger::PreLaunch, BackupTrigger::PostExit, BackupTrigger::PreRestore, BackupTrigger::Periodic]
+            .into_iter()
+            .find(|t| t.label() == label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Modification time in Unix milliseconds, used by the store to skip unchanged files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<i64>,
+    /// Chunk hashes making up the file, in order. Empty in zip archives.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupManifest {
+    pub version: u32,
+    pub created_at: DateTime<Local>,
+    pub trigger: BackupTrigger,
+    pub worlds: Vec<String>,
+    pub files: Vec<ManifestFile>,
+    /// Files left out because they kept changing while being read.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    /// A zip archive in the backups folder.
+    Archive,
+    /// A snapshot in the deduplicated store.
+    Snapshot,
+}
+
+/// A backup as shown in the backup browser.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub name: String,
+    pub kind: BackupKind,
+    pub path: PathBuf,
+    pub created_at: DateTime<Local>,
+    /// Archive size in bytes; for snapshots, the size of the files they hold,
+    /// most of which is shared with other snapshots.
+    pub size: u64,
+    pub trigger: Option<BackupTrigger>,
+    pub worlds: Vec<String>,
+    pub file_count: usize,
+    /// Files left out because they kept changing while being read.
+    pub skipped: Vec<String>,
+    /// False for archives without a manifest, which cannot be verified.
+    pub has_manifest: bool,
+}
+
+/// Outcome of checking an archive against its manifest.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupVerification {
+    pub name: String,
+    pub ok: bool,
+    pub checked_files: usize,
+    /// Files whose content does not match the recorded hash or size.
+    pub corrupted: Vec<String>,
+    /// Files listed in the manifest but absent from the archive.
+    /// Missing chunks of a snapshot are reported as corrupted files instead.
+    pub missing: Vec<String>,
+    /// Archive entries that are not in the manifest.
+    pub unexpected: Vec<String>,
+}
+
+/// Sent as `backup-progress` while a backup is written.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    pub trigger: BackupTrigger,
+    pub files_done: usize,
+    pub files_total: usize,
+    /// Bytes read so far, including files read again after they changed.
+    pub bytes_read: u64,
+    /// Files left out because they kept changing or could not be opened.
+    pub skipped: usize,
+}
+
+/// Sent as `backup-finished` once a backup was written or failed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupOutcome {
+    pub trigger: BackupTrigger,
+    /// Name of the new backup; `None` when there was nothing to back up or it failed.
+    pub name: Option<String>,
+    pub skipped: usize,
+    pub error: Option<String>,
+}
+
+/// Rate limiting and progress reporting for one backup run.
+pub struct BackupIo {
+    max_bytes_per_sec: Option<u64>,
+    started: Instant,
+    last_report: Option<Instant>,
+    progress: BackupProgress,
+    on_progress: Box<dyn FnMut(&BackupProgress) + Send>,
+}
+
+impl BackupIo {
+    pub fn new(trigger: BackupTrigger) -> Self {
+        Self {
+            max_bytes_per_sec: None,
+            started: Instant::now(),
+            last_report: None,
+            progress: BackupProgress { trigger, files_done: 0, files_total: 0, bytes_read: 0, skipped: 0 },
+            on_progress: Box::new(|_| {}),
+        }
+    }
+
+    /// Caps reads at `mb_per_sec`; `None` or 0 means no limit.
+    pub fn with_limit(mut self, mb_per_sec: Option<u32>) -> Self {
+        self.max_bytes_per_sec = mb_per_sec.filter(|mb| *mb > 0).map(|mb| u64::from(mb) * 1024 * 1024);
+        self
+    }
+
+    pub fn on_progress(mut self, report: impl FnMut(&BackupProgress) + Send + 'static) -> Self {
+        self.on_progress = Box::new(report);
+        self
+    }
+
+    fn start(&mut self, files_total: usize) {
+        self.started = Instant::now();
+        self.progress.files_total = files_total;
+        self.report(true);
+    }
+
+    /// Accounts for `n` bytes read, sleeping when the run is ahead of the limit.
+    fn read(&mut self, n: usize) {
+        self.progress.bytes_read += n as u64;
+        if let Some(limit) = self.max_bytes_per_sec {
+            let due = Duration::from_secs_f64(self.progress.bytes_read as f64 / limit as f64);
+            let elapsed = self.started.elapsed();
+            if due > elapsed {
+                std::thread::sleep(due - elapsed);
+            }
+        }
+    }
+
+    pub fn progress(&self) -> &BackupProgress {
+        &self.progress
+    }
+
+    pub(crate) fn file_done(&mut self, stored: bool) {
+        self.progress.files_done += 1;
+        if !stored {
+            self.progress.skipped += 1;
+        }
+        let last = self.progress.files_done == self.progress.files_total;
+        self.report(last);
+    }
+
+    fn report(&mut self, force: bool) {
+        let due = self.last_report.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL);
+        if force || due {
+            self.last_report = Some(Instant::now());
+            (self.on_progress)(&self.progress);
+        }
+    }
+}
+
+/// Counts reads against a `BackupIo`.
+pub(crate) struct ThrottledReader<'a, R> {
+    inner: R,
+    io: &'a mut BackupIo,
+}
+
+impl<'a, R: Read> ThrottledReader<'a, R> {
+    pub fn new(inner: R, io: &'a mut BackupIo) -> Self {
+        Self { inner, io }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.io.read(n);
+        Ok(n)
+    }
+}
+
+/// Size and modification time of a file, compared before and after copying it
+/// to notice writes that happened meanwhile.
+#[derive(PartialEq)]
+struct FileStamp(u64, Option<SystemTime>);
+
+impl FileStamp {
+    fn of(path: &Path) -> io::Result<Self> {
+        let meta = fs::metadata(path)?;
+        Ok(Self(meta.len(), meta.modified().ok()))
+    }
+}
+
+/// Copies `path` with `copy` until the file stays unchanged for a whole read,
+/// trying `READ_ATTEMPTS` times. `discard` undoes a copy that turned out
+/// inconsistent. Returns `None` when the file never settled or could not be
+/// opened, in which case the caller should leave it out.
+pub(crate) fn read_consistent<T, C>(
+    path: &Path,
+    ctx: &mut C,
+    mut copy: impl FnMut(&mut C, File) -> io::Result<T>,
+    mut discard: impl FnMut(&mut C) -> io::Result<()>,
+) -> io::Result<Option<T>> {
+    for attempt in 1..=READ_ATTEMPTS {
+        // A file being written by the game can fail to open on Windows
+        let opened = FileStamp::of(path).and_then(|stamp| Ok((stamp, File::open(path)?)));
+        let (before, file) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!("Skipping {:?} in backup: {}", path, e);
+                return Ok(None);
+            }
+        };
+        let copied = copy(ctx, file)?;
+        if FileStamp::of(path).is_ok_and(|after| after == before) {
+            return Ok(Some(copied));
+        }
+        discard(ctx)?;
+        if attempt < READ_ATTEMPTS {
+            info!("{:?} changed while being backed up, retrying", path);
+            std::thread::sleep(RETRY_DELAY);
+        }
+    }
+    warn!("Skipping {:?} in backup: it kept changing", path);
+    Ok(None)
+}
+
+/// Folder holding the backup archives.
+pub fn backups_dir(launcher_opts: &LauncherOptions) -> PathBuf {
+    launcher_opts
+        .launcher_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(".permadeath-launcher"))
+        .join("backups")
+}
+
+// ---------------------------------------------------------------------------
+// Creating backups
+// ---------------------------------------------------------------------------
+
+/// Backs up the worlds and client settings of `game_dir`, with a manifest of
+/// SHA-256 hashes used to verify it later. Depending on `backup_format` this is
+/// a snapshot `<date>_<trigger>` in the deduplicated store or an archive
+/// `launcher_dir/backups/<date>_<trigger>.zip`. Old backups are not pruned
+/// here; see `prune`.
+///
+/// Returns the backup's name, or `None` when there is nothing to back up yet
+/// (first launch).
+pub fn create_backup(
+    launcher_opts: &LauncherOptions,
+    game_dir: &Path,
+    trigger: BackupTrigger,
+) -> Result<Option<String>, String> {
+    let sources: Vec<String> = BACKUP_SOURCES.iter().map(|s| s.to_string()).collect();
+    create_backup_of(launcher_opts, game_dir, trigger, &sources, &mut BackupIo::new(trigger))
+}
+
+/// Like `create_backup`, for the given folders and files of `game_dir` and
+/// with the rate limit and progress reporting of `io`. Files that keep changing
+/// while they are read are left out and listed in the manifest.
+pub fn create_backup_of(
+    launcher_opts: &LauncherOptions,
+    game_dir: &Path,
+    trigger: BackupTrigger,
+    sources: &[String],
+    io: &mut BackupIo,
+) -> Result<Option<String>, String> {
+    let sources: Vec<PathBuf> = sources
+        .iter()
+        .filter(|s| {
+            // Only paths inside game_dir
+            let relative = Path::new(s.as_str()).components().all(|c| matches!(c, Component::Normal(_)));
+            if !relative {
+                warn!("Ignoring backup source outside the game directory: {}", s);
+            }
+            relative
+        })
+        .map(|s| game_dir.join(s))
+        .filter(|p| p.exists())
+        .collect();
+    if sources.is_empty() {
+        info!("Nothing to back up in {:?}", game_dir);
+        return Ok(None);
+    }
+
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let dir = backups_dir(launcher_opts);
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    for source in &sources {
+        collect_files(source, &mut files).map_err(|e| format!("Backup failed: {}", e))?;
+    }
+    io.start(files.len());
+
+    let created_at = Local::now();
+    let id = format!("{}_{}", created_at.format("%Y-%m-%d_%H-%M-%S"), trigger.label());
+    let manifest = BackupManifest {
+        version: MANIFEST_VERSION,
+        created_at,
+        trigger,
+        worlds: Vec::new(),
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    if launcher_opts.backup_format == BackupFormat::Incremental {
+        BackupStore::new(&dir)
+            .create_snapshot(&id, game_dir, &files, manifest, io)
+            .map_err(|e| format!("Backup failed: {}", e))?;
+        return Ok(Some(id));
+    }
+
+    let name = format!("{}.zip", id);
+    let path = dir.join(&name);
+    // Written under a temporary name so an interrupted backup is never listed
+    let partial = dir.join(format!("{}.part", name));
+
+    let result = write_archive(&partial, game_dir, &files, manifest, io);
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Backup failed: {}", e));
+    }
+    fs::rename(&partial, &path).map_err(|e| format!("Could not finish the backup: {e}"))?;
+
+    info!("Backup {:?} created", path);
+    Ok(Some(name))
+}
+
+impl BackupManifest {
+    /// Relative path of `file` as stored in manifests, with `/` separators.
+    pub(crate) fn entry_name(game_dir: &Path, file: &Path) -> String {
+        file.strip_prefix(game_dir)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Records a backed-up file, and the world it belongs to.
+    pub(crate) fn add_file(&mut self, file: ManifestFile) {
+        if let Some(world) = world_of(&file.path) {
+            if !self.worlds.iter().any(|w| w == world) {
+                self.worlds.push(world.to_string());
+            }
+        }
+        self.files.push(file);
+    }
+}
+
+fn write_archive(
+    archive: &Path,
+    game_dir: &Path,
+    files: &[PathBuf],
+    mut manifest: BackupManifest,
+    io: &mut BackupIo,
+) -> io::Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(archive)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file in files {
+        let name = BackupManifest::entry_name(game_dir, file);
+        let copied = read_consistent(
+            file,
+            &mut (&mut zip, &mut *io),
+            |(zip, io), input| {
+                let large = input.metadata().map(|m| m.len() >= u32::MAX as u64).unwrap_or(false);
+                zip.start_file(name.as_str(), options.large_file(large))?;
+                copy_hashed(&mut ThrottledReader::new(input, io), zip)
+            },
+            |(zip, _)| zip.abort_file().map_err(io::Error::other),
+        )?;
+        io.file_done(copied.is_some());
+
+        // One unreadable file should not cost the whole backup
+        match copied {
+            Some((size, sha256)) => manifest.add_file(ManifestFile {
+                path: name,
+                size,
+                sha256,
+                modified: None,
+                chunks: Vec::new(),
+            }),
+            None => manifest.skipped.push(name),
+        }
+    }
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    serde_json::to_writer_pretty(&mut zip, &manifest)?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Copies `input` to `output` and returns the byte count and SHA-256 of the data.
+fn copy_hashed(input: &mut impl Read, output: &mut impl Write) -> io::Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = input.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        output.write_all(&buf[..n])?;
+        size += n as u64;
+    }
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// Regular files under `path` (or `path` itself), skipping symlinks.
+fn collect_files(path: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_file() {
+        let skipped = path
+            .file_name()
+            .is_some_and(|n| SKIPPED_FILES.iter().any(|s| n == *s));
+        if !skipped {
+            out.push(path.to_path_buf());
+        }
+    } else if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_files(&entry?.path(), out)?;
+        }
+    }
+    Ok(())
+}
+
+/// The world folder an archive entry belongs to (`saves/<world>/...`).
+fn world_of(entry: &str) -> Option<&str> {
+    let mut parts = entry.strip_prefix("saves/")?.splitn(2, '/');
+    let world = parts.next()?;
+    parts.next().map(|_| world)
+}
+
+// ---------------------------------------------------------------------------
+// Retention
+// ---------------------------------------------------------------------------
+
+/// A backup found on disk, for retention.
+struct StoredBackup {
+    name: String,
+    kind: BackupKind,
+    path: PathBuf,
+    /// Archive size; 0 for snapshots, whose chunks are counted for the whole store.
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Archives and snapshots, newest first.
+fn stored_backups(launcher_opts: &LauncherOptions) -> Vec<StoredBackup> {
+    let dir = backups_dir(launcher_opts);
+    let store = BackupStore::new(&dir);
+
+    let mut backups: Vec<StoredBackup> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "zip"))
+                .filter_map(|p| {
+                    let meta = fs::metadata(&p).ok()?;
+                    Some(StoredBackup {
+                        name: p.file_name()?.to_string_lossy().to_string(),
+                        kind: BackupKind::Archive,
+                        size: meta.len(),
+                        modified: meta.modified().ok()?,
+                        path: p,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for id in store.list() {
+        let path = store.snapshot_path(&id);
+        if let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) {
+            backups.push(StoredBackup { name: id, kind: BackupKind::Snapshot, path, size: 0, modified });
+        }
+    }
+
+    // Names start with the date, so sorting by name sorts by age
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+fn remove_backup(store: &BackupStore, backup: &StoredBackup) -> bool {
+    let result = match backup.kind {
+        BackupKind::Archive => fs::remove_file(&backup.path),
+        BackupKind::Snapshot => store.delete_snapshot(&backup.name),
+    };
+    match result {
+        Ok(_) => {
+            info!("Removed old backup {}", backup.name);
+            true
+        }
+        Err(e) => {
+            warn!("Could not remove old backup {}: {}", backup.name, e);
+            false
+        }
+    }
+}
+
+/// Deletes backups that fall outside `backup_retention`, then the store chunks
+/// no remaining snapshot uses. Returns how many backups were removed.
+pub fn prune(launcher_opts: &LauncherOptions) -> usize {
+    let _guard = BACKUP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let retention = &launcher_opts.backup_retention;
+    let store = BackupStore::new(&backups_dir(launcher_opts));
+
+    let max_age = retention
+        .max_age_days
+        .map(|days| Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+
+    let mut removed = 0;
+    let mut kept = Vec::new();
+    for (index, backup) in stored_backups(launcher_opts).into_iter().enumerate() {
+        let too_many = index >= retention.keep_last.max(1);
+        let too_old = max_age.is_some_and(|max| backup.modified.elapsed().unwrap_or_default() > max);
+
+        if index > 0 && (too_many || too_old) && remove_backup(&store, &backup) {
+            removed += 1;
+        } else {
+            kept.push(backup);
+        }
+    }
+    if removed > 0 {
+        store.gc();
+    }
+
+    // Snapshots share chunks, so the size limit is checked against what is
+    // actually on disk after each removal
+    if let Some(max_total) = retention.max_total_mb.map(|mb| mb * 1024 * 1024) {
+        while kept.len() > 1 {
+            let total = kept.iter().map(|b| b.size).sum::<u64>() + store.disk_usage();
+            if total <= max_total {
+                break;
+            }
+            let oldest = kept.pop().expect("more than one backup is kept");
+            if !remove_backup(&store, &oldest) {
+                break;
+            }
+            removed += 1;
+            if oldest.kind == BackupKind::Snapshot {
+                store.gc();
+            }
+        }
+    }
+    removed
+}
+
+// ---------------------------------------------------------------------------
+// Browsing and verifying
+// ---------------------------------------------------------------------------
+
+/// Archives are named `<id>.zip`; every other name refers to a snapshot.
+fn is_archive(name: &str) -> bool {
+    name.ends_with(".zip")
+}
+
+/// Whether `name` is a bare file name, so it cannot point outside the backups folder.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && Path::new(name).file_name().is_some_and(|f| f == name)
+}
+
+/// Path of the backup called `name`, refusing anything that is not a plain
+/// archive name inside the backups folder.
+fn backup_path(launcher_opts: &LauncherOptions, name: &str) -> Result<PathBuf, String> {
+    let path = backups_dir(launcher_opts).join(name);
+    if !is_plain_name(name) || !is_archive(name) || !path.is_file() {
+        return Err(format!("Backup '{}' was not found.", name));
+    }
+    Ok(path)
+}
+
+/// Manifest of the snapshot called `name` in the store.
+fn snapshot_manifest(store: &BackupStore, name: &str) -> Result<BackupManifest, String> {
+    if !is_plain_name(name) || is_archive(name) {
+        return Err(format!("Backup '{}' was not found.", name));
+    }
+    store
+        .read_snapshot(name)
+        .ok_or_else(|| format!("Backup '{}' was not found.", name))
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<BufReader<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| format!("{} is not a valid archive: {}", path.display(), e))
+}
+
+fn read_manifest(archive: &mut ZipArchive<BufReader<File>>) -> Option<BackupManifest> {
+    let entry = archive.by_name(MANIFEST_ENTRY).ok()?;
+    serde_json::from_reader(entry).ok()
+}
+
+/// Backups in the backups folder, newest first.
+pub fn list_backups(launcher_opts: &LauncherOptions) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(backups_dir(launcher_opts))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "zip"))
+                .filter_map(|p| match backup_info(&p) {
+                    Ok(info) => Some(info),
+                    Err(e) => {
+                        warn!("Skipping unreadable backup: {}", e);
+                        None
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let store = BackupStore::new(&backups_dir(launcher_opts));
+    for id in store.list() {
+        match store.read_snapshot(&id) {
+            Some(manifest) => backups.push(BackupInfo {
+                path: store.snapshot_path(&id),
+                name: id,
+                kind: BackupKind::Snapshot,
+                created_at: manifest.created_at,
+                size: manifest.files.iter().map(|f| f.size).sum(),
+                trigger: Some(manifest.trigger),
+                worlds: manifest.worlds,
+                file_count: manifest.files.len(),
+                skipped: manifest.skipped,
+                has_manifest: true,
+            }),
+            None => warn!("Skipping unreadable snapshot {}", id),
+        }
+    }
+
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo, String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    let mut archive = open_archive(path)?;
+
+    if let Some(manifest) = read_manifest(&mut archive) {
+        return Ok(BackupInfo {
+            name,
+            kind: BackupKind::Archive,
+            path: path.to_path_buf(),
+            created_at: manifest.created_at,
+            size: meta.len(),
+            trigger: Some(manifest.trigger),
+            worlds: manifest.worlds,
+            file_count: manifest.files.len(),
+            skipped: manifest.skipped,
+            has_manifest: true,
+        });
+    }
+
+    // Archives made before manifests existed: derive what we can from the entries
+    let mut worlds: Vec<String> = Vec::new();
+    for entry in archive.file_names() {
+        if let Some(world) = world_of(entry) {
+            if !worlds.iter().any(|w| w == world) {
+                worlds.push(world.to_string());
+            }
+        }
+    }
+    let trigger = name
+        .trim_end_matches(".zip")
+        .splitn(3, '_')
+        .nth(2)
+        .and_then(BackupTrigger::from_label);
+
+    Ok(BackupInfo {
+        created_at: meta.modified().map(DateTime::<Local>::from).unwrap_or_else(|_| Local::now()),
+        path: path.to_path_buf(),
+        name,
+        kind: BackupKind::Archive,
+        size: meta.len(),
+        trigger,
+        worlds,
+        file_count: archive.len(),
+        skipped: Vec::new(),
+        has_manifest: false,
+    })
+}
+
+/// Re-reads every file of a backup and compares it with the hashes recorded
+/// when it was taken.
+pub fn verify_backup(launcher_opts: &LauncherOptions, name: &str) -> Result<BackupVerification, String> {
+    let mut result = BackupVerification {
+        name: name.to_string(),
+        ok: true,
+        checked_files: 0,
+        corrupted: Vec::new(),
+        missing: Vec::new(),
+        unexpected: Vec::new(),
+    };
+
+    if is_archive(name) {
+        verify_archive(launcher_opts, name, &mut result)?;
+    } else {
+        let store = BackupStore::new(&backups_dir(launcher_opts));
+        let manifest = snapshot_manifest(&store, name)?;
+        for file in &manifest.files {
+            if !store.verify_file(file) {
+                result.corrupted.push(file.path.clone());
+            }
+            result.checked_files += 1;
+        }
+    }
+
+    result.ok = result.corrupted.is_empty() && result.missing.is_empty() && result.unexpected.is_empty();
+    info!(
+        "Verified backup {}: ok={} ({} corrupted, {} missing, {} unexpected)",
+        name,
+        result.ok,
+        result.corrupted.len(),
+        result.missing.len(),
+        result.unexpected.len()
+    );
+    Ok(result)
+}
+
+fn verify_archive(launcher_opts: &LauncherOptions, name: &str, result: &mut BackupVerification) -> Result<(), String> {
+    let path = backup_path(launcher_opts, name)?;
+    let mut archive = open_archive(&path)?;
+    let manifest = read_manifest(&mut archive)
+        .ok_or_else(|| format!("Backup '{}' has no manifest and cannot be verified.", name))?;
+
+    for expected in &manifest.files {
+        let mut entry = match archive.by_name(&expected.path) {
+            Ok(entry) => entry,
+            Err(_) => {
+                result.missing.push(expected.path.clone());
+                continue;
+            }
+        };
+        // Reading to the end also checks the entry's CRC
+        match copy_hashed(&mut entry, &mut io::sink()) {
+            Ok((size, sha256)) if size == expected.size && sha256 == expected.sha256 => {}
+            _ => result.corrupted.push(expected.path.clone()),
+        }
+        result.checked_files += 1;
+    }
+
+    for entry in archive.file_names() {
+        if entry != MANIFEST_ENTRY && !manifest.files.iter().any(|f| f.path == entry) {
+            result.unexpected.push(entry.to_string());
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Restoring
+// ---------------------------------------------------------------------------
+
+/// Restores a backup into `game_dir`: every backed-up folder and file, or only
+/// `saves/<world>` when `world` is given.
+///
+/// The backup is verified first, and the current files are backed up with the
+/// `PreRestore` trigger before anything is replaced. Returns that safety backup.
+/// The `RestoreGuard` keeps the game from being launched meanwhile.
+pub fn restore_backup(
+    _guard: &RestoreGuard,
+    launcher_opts: &LauncherOptions,
+    game_dir: &Path,
+    name: &str,
+    world: Option<&str>,
+) -> Result<Option<String>, String> {
+    let verification = verify_backup(launcher_opts, name)?;
+    if !verification.ok {
+        return Err(format!("Backup '{}' is damaged and was not restored.", name));
+    }
+
+    let store = BackupStore::new(&backups_dir(launcher_opts));
+    let mut archive = None;
+    let manifest = if is_archive(name) {
+        let mut opened = open_archive(&backup_path(launcher_opts, name)?)?;
+        let manifest = read_manifest(&mut opened).ok_or_else(|| "The backup manifest disappeared.".to_string())?;
+        archive = Some(opened);
+        manifest
+    } else {
+        snapshot_manifest(&store, name)?
+    };
+
+    // The folders and files that will be replaced, relative to game_dir
+    let targets: Vec<String> = match world {
+        Some(world) => {
+            if !manifest.worlds.iter().any(|w| w == world) {
+                return Err(format!("Backup '{}' does not contain the world '{}'.", name, world));
+            }
+            vec![format!("saves/{}", world)]
+        }
+        // Everything the backup holds, by top-level folder or file
+        None => {
+            let mut targets: Vec<String> = Vec::new();
+            for file in &manifest.files {
+                let top = file.path.split('/').next().unwrap_or_default().to_string();
+                if !targets.contains(&top) {
+                    targets.push(top);
+                }
+            }
+            targets
+        }
+    };
+
+    let snapshot = create_backup(launcher_opts, game_dir, BackupTrigger::PreRestore)?;
+
+    let staging = game_dir.join(RESTORE_STAGING_DIR);
+    let _ = fs::remove_dir_all(&staging);
+    let extracted = match archive.as_mut() {
+        Some(archive) => extract(archive, &manifest, &targets, &staging),
+        None => extract_snapshot(&store, &manifest, &targets, &staging),
+    };
+    if let Err(e) = extracted {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Could not extract the backup: {}", e));
+    }
+
+    for target in &targets {
+        let live = game_dir.join(target);
+        let staged = staging.join(target);
+        let removed = if live.is_dir() {
+            fs::remove_dir_all(&live)
+        } else if live.exists() {
+            fs::remove_file(&live)
+        } else {
+            Ok(())
+        };
+        let moved = removed.and_then(|_| {
+            if let Some(parent) = live.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&staged, &live)
+        });
+        if let Err(e) = moved {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!(
+                "Restoring {} failed: {}. The previous files are in the safety backup.",
+                target, e
+            ));
+        }
+    }
+
+    let _ = fs::remove_dir_all(&staging);
+    info!("Restored backup {} ({:?}) into {:?}", name, targets, game_dir);
+    Ok(snapshot)
+}
+
+fn is_target(file: &ManifestFile, targets: &[String]) -> bool {
+    targets
+        .iter()
+        .any(|t| file.path == *t || file.path.starts_with(&format!("{}/", t)))
+}
+
+fn extract(
+    archive: &mut ZipArchive<BufReader<File>>,
+    manifest: &BackupManifest,
+    targets: &[String],
+    staging: &Path,
+) -> io::Result<()> {
+    for file in manifest.files.iter().filter(|f| is_target(f, targets)) {
+        let mut entry = archive.by_name(&file.path).map_err(io::Error::other)?;
+        // Guards against entries escaping the staging folder (`../`)
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| io::Error::other(format!("unsafe path in archive: {}", file.path)))?;
+        let out_path = staging.join(relative);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = BufWriter::new(File::create(&out_path)?);
+        io::copy(&mut entry, &mut out)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn extract_snapshot(
+    store: &BackupStore,
+    manifest: &BackupManifest,
+    targets: &[String],
+    staging: &Path,
+) -> io::Result<()> {
+    for file in manifest.files.iter().filter(|f| is_target(f, targets)) {
+        // Same guard as `enclosed_name` for archives: only plain relative paths
+        let relative = Path::new(&file.path);
+        let safe = relative.components().all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return Err(io::Error::other(format!("unsafe path in snapshot: {}", file.path)));
+        }
+        let out_path = staging.join(relative);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        store.restore_file(file, &out_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `restore_backup` needs the one `RestoreGuard`, so tests that restore
+    /// take turns.
+    static RESTORE_TESTS: Mutex<()> = Mutex::new(());
+
+    /// Launcher options with their own backups folder, and a game directory
+    /// holding two worlds and the client settings.
+    fn setup(format: BackupFormat) -> (LauncherOptions, PathBuf) {
+        let root = std::env::temp_dir().join(format!("backup-service-{}", uuid::Uuid::new_v4()));
+        let game_dir = root.join("game");
+        fs::create_dir_all(game_dir.join("saves/World1/region")).unwrap();
+        fs::create_dir_all(game_dir.join("saves/World2")).unwrap();
+        fs::write(game_dir.join("saves/World1/level.dat"), b"world one").unwrap();
+        fs::write(game_dir.join("saves/World1/region/r.0.0.mca"), vec![1u8; 10_000]).unwrap();
+        fs::write(game_dir.join("saves/World2/level.dat"), b"world two").unwrap();
+        fs::write(game_dir.join("options.txt"), b"fov:70").unwrap();
+
+        let mut opts = LauncherOptions::new();
+        opts.launcher_dir = Some(root.join("launcher"));
+        opts.backup_format = format;
+        (opts, game_dir)
+    }
+
+    fn read(game_dir: &Path, file: &str) -> String {
+        fs::read_to_string(game_dir.join(file)).unwrap()
+    }
+
+    /// Changes every backed-up file and adds a world the backup does not have.
+    fn play(game_dir: &Path) {
+        fs::write(game_dir.join("saves/World1/level.dat"), b"world one, later").unwrap();
+        fs::write(game_dir.join("saves/World2/level.dat"), b"world two, later").unwrap();
+        fs::create_dir_all(game_dir.join("saves/World3")).unwrap();
+        fs::write(game_dir.join("saves/World3/level.dat"), b"world three").unwrap();
+        fs::write(game_dir.join("options.txt"), b"fov:90").unwrap();
+    }
+
+    #[test]
+    fn restores_the_whole_backup() {
+        let _turn = RESTORE_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+        for format in [BackupFormat::Incremental, BackupFormat::Zip] {
+            let (opts, game_dir) = setup(format);
+            let name = create_backup(&opts, &game_dir, BackupTrigger::PreLaunch).unwrap().unwrap();
+            play(&game_dir);
+
+            let guard = begin_restore().unwrap();
+            let safety = restore_backup(&guard, &opts, &game_dir, &name, None).unwrap();
+            drop(guard);
+
+            assert_eq!(read(&game_dir, "saves/World1/level.dat"), "world one");
+            assert_eq!(read(&game_dir, "saves/World2/level.dat"), "world two");
+            assert_eq!(read(&game_dir, "options.txt"), "fov:70");
+            assert_eq!(fs::read(game_dir.join("saves/World1/region/r.0.0.mca")).unwrap(), vec![1u8; 10_000]);
+            assert!(!game_dir.join("saves/World3").exists());
+            assert!(!game_dir.join(RESTORE_STAGING_DIR).exists());
+
+            // The safety backup holds the files as they were before
+            let safety = safety.unwrap();
+            assert!(list_backups(&opts).iter().any(|b| b.name == safety && b.worlds.len() == 3));
+        }
+    }
+
+    #[test]
+    fn restores_a_single_world() {
+        let _turn = RESTORE_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+        for format in [BackupFormat::Incremental, BackupFormat::Zip] {
+            let (opts, game_dir) = setup(format);
+            let name = create_backup(&opts, &game_dir, BackupTrigger::PreLaunch).unwrap().unwrap();
+            play(&game_dir);
+
+            let guard = begin_restore().unwrap();
+            restore_backup(&guard, &opts, &game_dir, &name, Some("World1")).unwrap();
+            let missing = restore_backup(&guard, &opts, &game_dir, &name, Some("World3"));
+            drop(guard);
+
+            assert_eq!(read(&game_dir, "saves/World1/level.dat"), "world one");
+            assert_eq!(read(&game_dir, "saves/World2/level.dat"), "world two, later");
+            assert_eq!(read(&game_dir, "saves/World3/level.dat"), "world three");
+            assert_eq!(read(&game_dir, "options.txt"), "fov:90");
+            assert!(missing.unwrap_err().contains("does not contain the world"));
+        }
+    }
+
+    /// Rewrites the archive `name` with different contents for `entry`,
+    /// keeping its manifest.
+    fn tamper_with_archive(opts: &LauncherOptions, name: &str, entry: &str) {
+        let path = backup_path(opts, name).unwrap();
+        let mut archive = open_archive(&path).unwrap();
+        let tampered = path.with_extension("tampered");
+        let mut zip = ZipWriter::new(File::create(&tampered).unwrap());
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index).unwrap();
+            if file.name() == entry {
+                zip.start_file(entry, SimpleFileOptions::default()).unwrap();
+                zip.write_all(b"tampered").unwrap();
+            } else {
+                zip.raw_copy_file(file).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+        fs::rename(&tampered, &path).unwrap();
+    }
+
+    /// Overwrites the first chunk of `entry` in the snapshot `name`.
+    fn tamper_with_snapshot(opts: &LauncherOptions, name: &str, entry: &str) {
+        let store = BackupStore::new(&backups_dir(opts));
+        let manifest = store.read_snapshot(name).unwrap();
+        let file = manifest.files.iter().find(|f| f.path == entry).unwrap();
+        let hash = &file.chunks[0];
+        let chunk = backups_dir(opts).join("store/chunks").join(&hash[..2]).join(hash);
+        fs::write(chunk, b"tampered").unwrap();
+    }
+
+    #[test]
+    fn refuses_to_restore_a_corrupted_backup() {
+        let _turn = RESTORE_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+        for format in [BackupFormat::Incremental, BackupFormat::Zip] {
+            let (opts, game_dir) = setup(format);
+            let name = create_backup(&opts, &game_dir, BackupTrigger::PreLaunch).unwrap().unwrap();
+            match format {
+                BackupFormat::Zip => tamper_with_archive(&opts, &name, "saves/World1/level.dat"),
+                BackupFormat::Incremental => tamper_with_snapshot(&opts, &name, "saves/World1/level.dat"),
+            }
+            play(&game_dir);
+
+            let verification = verify_backup(&opts, &name).unwrap();
+            assert!(!verification.ok);
+            assert_eq!(verification.corrupted, vec!["saves/World1/level.dat"]);
+
+            let guard = begin_restore().unwrap();
+            let error = restore_backup(&guard, &opts, &game_dir, &name, None).unwrap_err();
+            drop(guard);
+            assert!(error.contains("is damaged"), "{}", error);
+
+            // Nothing was replaced, and no safety backup was taken
+            assert_eq!(read(&game_dir, "saves/World1/level.dat"), "world one, later");
+            assert_eq!(read(&game_dir, "options.txt"), "fov:90");
+            assert_eq!(list_backups(&opts).len(), 1);
+        }
+    }
+
+    #[test]
+    fn retention_keeps_at_least_one_backup() {
+        for format in [BackupFormat::Incremental, BackupFormat::Zip] {
+            let (mut opts, game_dir) = setup(format);
+            for trigger in [BackupTrigger::PreLaunch, BackupTrigger::Periodic, BackupTrigger::PostExit] {
+                create_backup(&opts, &game_dir, trigger).unwrap().unwrap();
+            }
+            let newest = list_backups(&opts)[0].name.clone();
+
+            opts.backup_retention.keep_last = 0;
+            opts.backup_retention.max_age_days = Some(0);
+            opts.backup_retention.max_total_mb = Some(0);
+            assert_eq!(prune(&opts), 2);
+
+            let left: Vec<String> = list_backups(&opts).into_iter().map(|b| b.name).collect();
+            assert_eq!(left, vec![newest.clone()]);
+            assert!(verify_backup(&opts, &newest).unwrap().ok);
+        }
+    }
+}
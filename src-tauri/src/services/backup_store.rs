@@ -0,0 +1,378 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+
+use crate::services::backup_service::{self, BackupIo, BackupManifest, ManifestFile, ThrottledReader};
+
+/// Region files are rewritten in place, 4 KiB sector by sector, so fixed-size
+/// chunks line up between snapshots and an edit only costs the chunks it touches.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Content-addressed backup repository under `launcher_dir/backups/store`.
+///
+/// Files are split into chunks stored once under `chunks/<2 hex>/<sha256>`
+/// (deflate-compressed), and each snapshot is a manifest in `snapshots/`
+/// listing the chunks of every file. Unchanged files cost nothing in a new
+/// snapshot; chunks no snapshot refers to any more are removed by `gc`.
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+/// Result of a garbage collection run.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcStats {
+    pub removed_chunks: usize,
+    pub freed_bytes: u64,
+}
+
+impl BackupStore {
+    pub fn new(backups_dir: &Path) -> Self {
+        Self { root: backups_dir.join("store") }
+    }
+
+    fn snapshots_dir(&self) -> PathBuf {
+        self.root.join("snapshots")
+    }
+
+    fn chunks_dir(&self) -> PathBuf {
+        self.root.join("chunks")
+    }
+
+    /// Where a chunk is stored. Hashes come from manifests on disk, so anything
+    /// but a SHA-256 hex digest is refused rather than turned into a path.
+    fn chunk_path(&self, hash: &str) -> io::Result<PathBuf> {
+        if !is_chunk_hash(hash) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid chunk hash {:?}", hash)));
+        }
+        Ok(self.chunks_dir().join(&hash[..2]).join(hash))
+    }
+
+    pub fn snapshot_path(&self, id: &str) -> PathBuf {
+        self.snapshots_dir().join(format!("{}.json", id))
+    }
+
+    // -----------------------------------------------------------------------
+    // Snapshots
+    // -----------------------------------------------------------------------
+
+    /// Stores `files` (absolute paths under `game_dir`) as snapshot `id`,
+    /// filling in `manifest.files`. Files whose size and modification time
+    /// match the latest snapshot reuse its chunks without being read.
+    pub fn create_snapshot(
+        &self,
+        id: &str,
+        game_dir: &Path,
+        files: &[PathBuf],
+        mut manifest: BackupManifest,
+        io: &mut BackupIo,
+    ) -> io::Result<BackupManifest> {
+        fs::create_dir_all(self.snapshots_dir())?;
+        fs::create_dir_all(self.chunks_dir())?;
+
+        let previous: HashMap<String, ManifestFile> = self
+            .latest()
+            .map(|m| m.files.into_iter().map(|f| (f.path.clone(), f)).collect())
+            .unwrap_or_default();
+
+        let mut new_chunks = 0;
+        for file in files {
+            let name = BackupManifest::entry_name(game_dir, file);
+
+            if let Some(prev) = previous.get(&name) {
+                let (size, modified) = stamp(file);
+                if Some(prev.size) == size && prev.modified.is_some() && prev.modified == modified {
+                    manifest.add_file(prev.clone());
+                    io.file_done(true);
+                    continue;
+                }
+            }
+
+            // Chunks of a discarded read are left for `gc`
+            let stored = backup_service::read_consistent(
+                file,
+                io,
+                |io, input| self.put_file(&mut ThrottledReader::new(input, io)),
+                |_| Ok(()),
+            )?;
+            io.file_done(stored.is_some());
+
+            match stored {
+                Some((stored, added)) => {
+                    new_chunks += added;
+                    // Read once the content is known to match it
+                    let (_, modified) = stamp(file);
+                    manifest.add_file(ManifestFile { path: name, modified, ..stored });
+                }
+                None => manifest.skipped.push(name),
+            }
+        }
+
+        // The manifest is written last: a snapshot exists only once all its chunks do
+        let path = self.snapshot_path(id);
+        let partial = path.with_extension("json.part");
+        let mut out = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer_pretty(&mut out, &manifest)?;
+        out.flush()?;
+        drop(out);
+        fs::rename(&partial, &path)?;
+
+        info!("Snapshot {} stored: {} files, {} new chunks", id, manifest.files.len(), new_chunks);
+        Ok(manifest)
+    }
+
+    /// Splits `input` into chunks and stores the new ones. Returns the file's
+    /// entry (without path or modification time) and how many chunks were new.
+    fn put_file(&self, input: &mut impl Read) -> io::Result<(ManifestFile, usize)> {
+        let mut file_hasher = Sha256::new();
+        let mut chunks = Vec::new();
+        let mut size = 0u64;
+        let mut added = 0;
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = read_full(input, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            let data = &buf[..n];
+            file_hasher.update(data);
+            size += n as u64;
+
+            let hash = format!("{:x}", Sha256::digest(data));
+            if self.put_chunk(&hash, data)? {
+                added += 1;
+            }
+            chunks.push(hash);
+            if n < CHUNK_SIZE {
+                break;
+            }
+        }
+
+        let file = ManifestFile {
+            path: String::new(),
+            size,
+            sha256: format!("{:x}", file_hasher.finalize()),
+            modified: None,
+            chunks,
+        };
+        Ok((file, added))
+    }
+
+    /// Writes a chunk unless it is already stored. Returns true if it was new.
+    fn put_chunk(&self, hash: &str, data: &[u8]) -> io::Result<bool> {
+        let path = self.chunk_path(hash)?;
+        if path.exists() {
+            return Ok(false);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension("part");
+        let mut encoder = DeflateEncoder::new(File::create(&partial)?, Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()?.sync_all()?;
+        fs::rename(&partial, &path)?;
+        Ok(true)
+    }
+
+    /// Reads and decompresses a chunk, checking it still has the expected hash.
+    fn get_chunk(&self, hash: &str) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(CHUNK_SIZE);
+        DeflateDecoder::new(File::open(self.chunk_path(hash)?)?).read_to_end(&mut data)?;
+        if format!("{:x}", Sha256::digest(&data)) != hash {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("chunk {} is corrupted", hash)));
+        }
+        Ok(data)
+    }
+
+    /// Reads a snapshot manifest. One that lists a malformed chunk hash was
+    /// damaged or edited by hand and is treated as unreadable.
+    pub fn read_snapshot(&self, id: &str) -> Option<BackupManifest> {
+        let data = fs::read_to_string(self.snapshot_path(id)).ok()?;
+        let manifest: BackupManifest = serde_json::from_str(&data).ok()?;
+        if let Some(file) = manifest.files.iter().find(|f| !f.chunks.iter().all(|h| is_chunk_hash(h))) {
+            warn!("Snapshot {} lists an invalid chunk hash for {}", id, file.path);
+            return None;
+        }
+        Some(manifest)
+    }
+
+    /// Snapshot ids, newest first (ids start with the date).
+    pub fn list(&self) -> Vec<String> {
+        let mut ids: Vec<String> = fs::read_dir(self.snapshots_dir())
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json").map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        ids.sort_by(|a, b| b.cmp(a));
+        ids
+    }
+
+    fn latest(&self) -> Option<BackupManifest> {
+        self.list().first().and_then(|id| self.read_snapshot(id))
+    }
+
+    pub fn delete_snapshot(&self, id: &str) -> io::Result<()> {
+        fs::remove_file(self.snapshot_path(id))
+    }
+
+    /// Writes a stored file back out, verifying every chunk and the whole-file hash.
+    pub fn restore_file(&self, file: &ManifestFile, out_path: &Path) -> io::Result<()> {
+        let mut hasher = Sha256::new();
+        let mut out = BufWriter::new(File::create(out_path)?);
+        for hash in &file.chunks {
+            let data = self.get_chunk(hash)?;
+            hasher.update(&data);
+            out.write_all(&data)?;
+        }
+        out.flush()?;
+        if format!("{:x}", hasher.finalize()) != file.sha256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} does not match its hash", file.path)));
+        }
+        Ok(())
+    }
+
+    /// Checks that a stored file can be rebuilt with its recorded size and hash.
+    pub fn verify_file(&self, file: &ManifestFile) -> bool {
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        for hash in &file.chunks {
+            match self.get_chunk(hash) {
+                Ok(data) => {
+                    size += data.len() as u64;
+                    hasher.update(&data);
+                }
+                Err(_) => return false,
+            }
+        }
+        size == file.size && format!("{:x}", hasher.finalize()) == file.sha256
+    }
+
+    // -----------------------------------------------------------------------
+    // Maintenance
+    // -----------------------------------------------------------------------
+
+    /// Bytes used by the stored chunks.
+    pub fn disk_usage(&self) -> u64 {
+        chunk_files(&self.chunks_dir())
+            .iter()
+            .filter_map(|p| fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum()
+    }
+
+    /// Deletes chunks that no snapshot refers to. Snapshots whose manifest
+    /// cannot be read keep the whole store alive rather than lose data.
+    pub fn gc(&self) -> GcStats {
+        let mut referenced = HashSet::new();
+        for id in self.list() {
+            match self.read_snapshot(&id) {
+                Some(manifest) => {
+                    for file in manifest.files {
+                        referenced.extend(file.chunks);
+                    }
+                }
+                None => {
+                    warn!("Snapshot {} is unreadable, skipping garbage collection", id);
+                    return GcStats::default();
+                }
+            }
+        }
+
+        let mut stats = GcStats::default();
+        for path in chunk_files(&self.chunks_dir()) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            // Leftovers of interrupted writes are garbage too
+            let keep = !name.ends_with(".part") && referenced.contains(name.as_ref());
+            if keep {
+                continue;
+            }
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if fs::remove_file(&path).is_ok() {
+                stats.removed_chunks += 1;
+                stats.freed_bytes += size;
+            }
+        }
+
+        info!("Backup store GC removed {} chunks ({} bytes)", stats.removed_chunks, stats.freed_bytes);
+        stats
+    }
+}
+
+/// A lowercase SHA-256 hex digest, as chunks are named.
+fn is_chunk_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Size and modification time (Unix milliseconds) of a file.
+fn stamp(path: &Path) -> (Option<u64>, Option<i64>) {
+    let Ok(meta) = fs::metadata(path) else {
+        return (None, None);
+    };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64);
+    (Some(meta.len()), modified)
+}
+
+/// Every file in the two-level chunk folder.
+fn chunk_files(chunks_dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(chunks_dir)
+        .map(|dirs| {
+            dirs.filter_map(|d| d.ok())
+                .filter_map(|d| fs::read_dir(d.path()).ok())
+                .flat_map(|files| files.filter_map(|f| f.ok()).map(|f| f.path()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Fills `buf` as far as the input allows, so chunk boundaries don't depend
+/// on how the OS splits reads.
+fn read_full(input: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_hashes_must_be_sha256_hex() {
+        let hash = format!("{:x}", Sha256::digest(b"chunk"));
+        assert!(is_chunk_hash(&hash));
+        assert!(!is_chunk_hash(""));
+        assert!(!is_chunk_hash("a"));
+        assert!(!is_chunk_hash(&hash.to_uppercase()));
+        assert!(!is_chunk_hash(&hash[..63]));
+        assert!(!is_chunk_hash(&format!("é{}", &hash[2..])));
+        assert!(!is_chunk_hash(&format!("../{}", &hash[3..])));
+    }
+
+    #[test]
+    fn malformed_hash_is_invalid_data() {
+        let store = BackupStore::new(Path::new("backups"));
+        for hash in ["", "a", "é"] {
+            let error = store.get_chunk(hash).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
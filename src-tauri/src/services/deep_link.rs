@@ -0,0 +1,155 @@
+use std::sync::Mutex;
+use log::{info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+use crate::services::game_service::{GameManager, DEFAULT_PROFILE};
+
+/// URL scheme registered for the launcher (`permadeath://...`).
+pub const SCHEME: &str = "permadeath";
+
+/// What a `permadeath://` link asks the launcher to do.
+///
+/// Links come from outside the launcher (Discord announcements, web pages), so
+/// nothing is executed directly: the UI shows the action and asks the user to
+/// confirm it before calling the regular commands.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum DeepLinkAction {
+    /// `permadeath://launch?profile=default[&offline]`
+    Launch { profile: String, offline: bool },
+    /// `permadeath://redeem?code=INVITE`
+    Redeem { code: String },
+}
+
+/// The last deep link that has not been handled by the UI yet.
+///
+/// A link that starts the launcher arrives before the webview is listening for
+/// events, so the UI also asks for it once it has loaded.
+#[derive(Default)]
+pub struct PendingDeepLink(Mutex<Option<DeepLinkAction>>);
+
+impl PendingDeepLink {
+    pub fn take(&self) -> Option<DeepLinkAction> {
+        self.0.lock().unwrap().take()
+    }
+
+    fn set(&self, action: DeepLinkAction) {
+        *self.0.lock().unwrap() = Some(action);
+    }
+}
+
+/// Parses a `permadeath://` URL into the action it requests.
+pub fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported link scheme '{}'.", url.scheme()));
+    }
+
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    match url.host_str().unwrap_or_default() {
+        "launch" => {
+            let profile = query("profile").unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+            GameManager::check_profile(&profile)?;
+            let offline = matches!(query("offline").as_deref(), Some("" | "1" | "true"));
+            Ok(DeepLinkAction::Launch { profile, offline })
+        }
+        "redeem" => {
+            let code = query("code").unwrap_or_default();
+            let valid = !code.is_empty()
+                && code.len() <= 64
+                && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err("The link contains an invalid invitation code.".to_string());
+            }
+            Ok(DeepLinkAction::Redeem { code })
+        }
+        other => Err(format!("Unknown link action '{}'.", other)),
+    }
+}
+
+/// Handles links opened while the launcher runs or that started it: the action
+/// is stored for the UI, announced with a `deep-link` event and the main window
+/// is brought to the front so the user can confirm it.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        info!("Deep link received: {}", url);
+        let action = match parse(&url) {
+            Ok(action) => action,
+            Err(e) => {
+                warn!("Ignoring deep link {}: {}", url, e);
+                continue;
+            }
+        };
+        app.state::<PendingDeepLink>().set(action.clone());
+        let _ = app.emit("deep-link", action);
+
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.unminimize();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(url: &str) -> Result<DeepLinkAction, String> {
+        parse(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn launch_defaults_to_the_default_profile() {
+        match parse_str("permadeath://launch").unwrap() {
+            DeepLinkAction::Launch { profile, offline } => {
+                assert_eq!(profile, DEFAULT_PROFILE);
+                assert!(!offline);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+        assert!(matches!(
+            parse_str("permadeath://launch?offline").unwrap(),
+            DeepLinkAction::Launch { offline: true, .. }
+        ));
+    }
+
+    #[test]
+    fn percent_encoded_profile_is_decoded() {
+        let encoded = DEFAULT_PROFILE.bytes().map(|b| format!("%{:02X}", b)).collect::<String>();
+        match parse_str(&format!("permadeath://launch?profile={}", encoded)).unwrap() {
+            DeepLinkAction::Launch { profile, .. } => assert_eq!(profile, DEFAULT_PROFILE),
+            other => panic!("unexpected action {:?}", other),
+        }
+        assert!(parse_str("permadeath://launch?profile=default%20%2F..").is_err());
+    }
+
+    #[test]
+    fn unknown_action_and_scheme_are_rejected() {
+        assert_eq!(parse_str("permadeath://delete-world?name=x").unwrap_err(), "Unknown link action 'delete-world'.");
+        assert!(parse_str("https://launch?profile=default").is_err());
+    }
+
+    #[test]
+    fn malformed_query_is_rejected() {
+        assert!(parse_str("permadeath://launch?profile").is_err());
+        assert!(parse_str("permadeath://launch?profile=%ZZ").is_err());
+        assert!(parse_str("permadeath://redeem").is_err());
+        assert!(parse_str("permadeath://redeem?code=").is_err());
+        assert!(parse_str("permadeath://redeem?code=abc%00def").is_err());
+        assert!(parse_str(&format!("permadeath://redeem?code={}", "a".repeat(65))).is_err());
+    }
+
+    #[test]
+    fn redeem_keeps_a_valid_code() {
+        match parse_str("permadeath://redeem?code=INVITE-2024_x").unwrap() {
+            DeepLinkAction::Redeem { code } => assert_eq!(code, "INVITE-2024_x"),
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use log::{info, warn};
+use regex::Regex;
+
+use crate::models::options::LauncherOptions;
+
+/// Rules shipped with the launcher, used when no remote or cached copy is available.
+const BUNDLED_RULES: &str = include_str!("../../resources/diagnosis_rules.json");
+
+const CACHED_RULES_FILE: &str = "diagnosis_rules.json";
+
+/// Keeps a hung server from leaving the background fetch running all session.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct RulesFile {
+    version: u32,
+    rules: Vec<RuleDefinition>,
+}
+
+/// A known problem as written in the rules file.
+///
+/// `explanation` and `suggestion` may reference named capture groups of the
+/// pattern (`${name}`) and the placeholders `{max_ram}` and `{suggested_max_ram}`.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct RuleDefinition {
+    id: String,
+    pattern: String,
+    title: String,
+    explanation: String,
+    suggestion: String,
+}
+
+struct CompiledRule {
+    definition: RuleDefinition,
+    regex: Regex,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosisSource {
+    GameLog,
+    CrashReport,
+}
+
+/// A known problem detected in the game output, emitted as `game-diagnosis`.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDiagnosis {
+    pub rule_id: String,
+    pub title: String,
+    pub explanation: String,
+    pub suggestion: String,
+    pub matched_line: String,
+    pub source: DiagnosisSource,
+    pub crash_report: Option<PathBuf>,
+}
+
+/// Matches game output against the known-problem rules.
+/// Each rule fires at most once per session.
+pub struct DiagnosisEngine {
+    rules: Vec<CompiledRule>,
+    fired: HashSet<String>,
+    max_ram: u32,
+    /// `version` of the rules file in use.
+    version: u32,
+}
+
+impl DiagnosisEngine {
+    /// Loads the newest rules on disk: whichever of the cached copy of the
+    /// remote file and the bundled rules has the highest `version`. The remote
+    /// file is fetched by `refresh`, so a slow network never delays a launch.
+    pub fn load(launcher_opts: &LauncherOptions, max_ram: u32) -> Self {
+        let cached = std::fs::read_to_string(cache_path(launcher_opts))
+            .ok()
+            .and_then(|data| parse_rules(&data, "cached"));
+        let bundled = parse_rules(BUNDLED_RULES, "bundled").expect("bundled diagnosis rules must be valid");
+
+        // On a tie the cached copy wins, as it is the one last published
+        let rules = [Some(bundled), cached]
+            .into_iter()
+            .flatten()
+            .max_by_key(|file| file.version)
+            .expect("the bundled rules are always present");
+
+        Self::new(rules, max_ram)
+    }
+
+    /// Fetches the rules at `DIAGNOSIS_RULES_URL` in the background and caches
+    /// them. Newer rules are also swapped into `engine` for the running game.
+    pub fn refresh(engine: Arc<Mutex<Self>>, launcher_opts: &LauncherOptions) {
+        let cache_path = cache_path(launcher_opts);
+        tokio::spawn(async move {
+            let Some(file) = fetch_remote_rules(&cache_path).await else { return };
+            let mut engine = engine.lock().unwrap();
+            if file.version > engine.version {
+                let max_ram = engine.max_ram;
+                let fired = std::mem::take(&mut engine.fired);
+                *engine = Self { fired, ..Self::new(file, max_ram) };
+            }
+        });
+    }
+
+    fn new(file: RulesFile, max_ram: u32) -> Self {
+        let rules = file
+            .rules
+            .into_iter()
+            .filter_map(|definition| match Regex::new(&definition.pattern) {
+                Ok(regex) => Some(CompiledRule { definition, regex }),
+                Err(e) => {
+                    warn!("Skipping diagnosis rule '{}': invalid pattern: {}", definition.id, e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        info!("Loaded {} diagnosis rules (version {})", rules.len(), file.version);
+        Self { rules, fired: HashSet::new(), max_ram, version: file.version }
+    }
+
+    /// Checks one line of game output. Several rules may match the same line.
+    pub fn check_line(&mut self, line: &str) -> Vec<GameDiagnosis> {
+        self.check(line, DiagnosisSource::GameLog, None)
+    }
+
+    /// Checks crash reports written to `game_dir/crash-reports` since `since`.
+    pub fn check_crash_reports(&mut self, game_dir: &Path, since: SystemTime) -> Vec<GameDiagnosis> {
+        let entries = match std::fs::read_dir(game_dir.join("crash-reports")) {
+            Ok(e) => e,
+            Err(_) => return vec![],
+        };
+
+        let mut diagnoses = vec![];
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let is_new = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|modified| modified >= since)
+                .unwrap_or(false);
+            if !is_new {
+                continue;
+            }
+            if let Ok(report) = std::fs::read_to_string(&path) {
+                info!("Checking crash report {:?}", path);
+                diagnoses.extend(self.check(&report, DiagnosisSource::CrashReport, Some(&path)));
+            }
+        }
+        diagnoses
+    }
+
+    fn check(&mut self, text: &str, source: DiagnosisSource, crash_report: Option<&Path>) -> Vec<GameDiagnosis> {
+        let suggested_max_ram = (self.max_ram + 2048).to_string();
+        let max_ram = self.max_ram.to_string();
+        let mut diagnoses = vec![];
+
+        for rule in &self.rules {
+            if self.fired.contains(&rule.definition.id) {
+                continue;
+            }
+            let Some(caps) = rule.regex.captures(text) else { continue };
+
+            let fill = |template: &str| {
+                let mut expanded = String::new();
+                caps.expand(template, &mut expanded);
+                expanded
+                    .replace("{max_ram}", &max_ram)
+                    .replace("{suggested_max_ram}", &suggested_max_ram)
+            };
+
+            let matched = caps.get(0).map(|m| m.as_str()).unwrap_or_default();
+            let matched_line = text
+                .lines()
+                .find(|l| l.contains(matched.lines().next().unwrap_or_default()))
+                .unwrap_or(matched)
+                .trim()
+                .to_string();
+
+            info!("Diagnosis '{}' matched: {}", rule.definition.id, matched_line);
+            diagnoses.push(GameDiagnosis {
+                rule_id: rule.definition.id.clone(),
+                title: rule.definition.title.clone(),
+                explanation: fill(&rule.definition.explanation),
+                suggestion: fill(&rule.definition.suggestion),
+                matched_line,
+                source: source.clone(),
+                crash_report: crash_report.map(Path::to_path_buf),
+            });
+        }
+
+        for d in &diagnoses {
+            self.fired.insert(d.rule_id.clone());
+        }
+        diagnoses
+    }
+}
+
+fn cache_path(launcher_opts: &LauncherOptions) -> PathBuf {
+    launcher_opts
+        .launcher_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(".permadeath-launcher"))
+        .join(CACHED_RULES_FILE)
+}
+
+fn parse_rules(data: &str, origin: &str) -> Option<RulesFile> {
+    match serde_json::from_str::<RulesFile>(data) {
+        Ok(rules) => Some(rules),
+        Err(e) => {
+            warn!("Could not parse {} diagnosis rules: {}", origin, e);
+            None
+        }
+    }
+}
+
+/// Downloads the rules from `DIAGNOSIS_RULES_URL` and caches them.
+/// Returns `None` if the URL is not set, or the download fails or takes longer
+/// than `FETCH_TIMEOUT`.
+async fn fetch_remote_rules(cache_path: &Path) -> Option<RulesFile> {
+    let url = match std::env::var("DIAGNOSIS_RULES_URL") {
+        Ok(u) if !u.is_empty() => u,
+        _ => return None,
+    };
+
+    info!("Fetching diagnosis rules from {}", url);
+
+    let response = reqwest::Client::new().get(&url).timeout(FETCH_TIMEOUT).send().await;
+    let body = match response.and_then(|r| r.error_for_status()) {
+        Ok(r) => r.text().await.ok()?,
+        Err(e) => {
+            warn!("Could not download diagnosis rules: {}", e);
+            return None;
+        }
+    };
+
+    let rules = parse_rules(&body, "remote")?;
+
+    if let Some(parent) = cache_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(e) = std::fs::write(cache_path, &body) {
+        warn!("Could not cache diagnosis rules: {}", e);
+    }
+
+    Some(rules)
+}
@@ -0,0 +1,81 @@
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Where `GameManager` reports game events.
+///
+/// With the UI running, events go to the webview windows through Tauri. In
+/// headless mode there is no window, so they are printed to the terminal instead.
+#[derive(Clone)]
+pub enum EventSink {
+    App(AppHandle),
+    Console(ConsoleSink),
+}
+
+impl EventSink {
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        match self {
+            EventSink::App(app) => {
+                let _ = app.emit(event, payload);
+            }
+            EventSink::Console(console) => console.print(event, &payload),
+        }
+    }
+
+    /// The Tauri app handle, when there is a UI to manage windows for.
+    pub fn app(&self) -> Option<&AppHandle> {
+        match self {
+            EventSink::App(app) => Some(app),
+            EventSink::Console(_) => None,
+        }
+    }
+}
+
+/// Prints game events to stdout/stderr and remembers the game's exit code.
+#[derive(Clone, Default)]
+pub struct ConsoleSink {
+    exit_code: Arc<Mutex<Option<i32>>>,
+}
+
+impl ConsoleSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The exit code of the game, once a `game-exited` event was received.
+    pub fn exit_code(&self) -> Option<i32> {
+        *self.exit_code.lock().unwrap()
+    }
+
+    fn print<S: Serialize>(&self, event: &str, payload: &S) {
+        let value = serde_json::to_value(payload).unwrap_or_default();
+        match event {
+            "game-log" => {
+                let line = value["line"].as_str().unwrap_or_default();
+                if value["isError"].as_bool().unwrap_or(false) {
+                    eprintln!("{}", line);
+                } else {
+                    println!("{}", line);
+                }
+            }
+            "game-state" => println!("[launcher] Game state: {}", value.as_str().unwrap_or_default()),
+            "game-exited" => {
+                let code = value.as_i64().unwrap_or(-1) as i32;
+                *self.exit_code.lock().unwrap() = Some(code);
+                println!("[launcher] Game exited with code {}", code);
+            }
+            "game-diagnosis" => {
+                eprintln!(
+                    "[launcher] Problem detected: {}\n           {}\n           Suggested fix: {}",
+                    value["title"].as_str().unwrap_or_default(),
+                    value["explanation"].as_str().unwrap_or_default(),
+                    value["suggestion"].as_str().unwrap_or_default(),
+                );
+            }
+            // Periodic samples would drown the game output, and records and
+            // backup results repeat the lines already printed from `game-log`
+            "game-metrics" | "game-log-record" | "backup-progress" | "backup-finished" => {}
+            _ => println!("[launcher] {}: {}", event, value),
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use log::{error, info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::services::game_lock::{GameLock, LOCK_FILE};
+
+/// Kept free on the target drive on top of the game files.
+const SPACE_MARGIN: u64 = 64 * 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+static MOVING: AtomicBool = AtomicBool::new(false);
+
+/// Whether a game directory move is running. Launches and restores wait for it.
+pub fn in_progress() -> bool {
+    MOVING.load(Ordering::SeqCst)
+}
+
+/// Proof that this caller is the only move running. Clears `MOVING` however
+/// the move ends.
+pub struct MoveGuard(());
+
+/// Marks a move as started. Call it under `GameManager::while_idle` so a
+/// launch cannot begin at the same time.
+pub fn begin() -> Result<MoveGuard, String> {
+    if MOVING.swap(true, Ordering::SeqCst) {
+        return Err("The game directory is already being moved.".to_string());
+    }
+    Ok(MoveGuard(()))
+}
+
+impl Drop for MoveGuard {
+    fn drop(&mut self) {
+        MOVING.store(false, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MovePhase {
+    Copying,
+    Verifying,
+    RemovingSource,
+    RollingBack,
+}
+
+/// Sent as `game-dir-move-progress` while the files are moved.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveProgress {
+    pub phase: MovePhase,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveOutcome {
+    pub game_dir: PathBuf,
+    pub files: usize,
+    pub bytes: u64,
+    /// Files left in the old folder: kept on purpose, or not removable.
+    pub source_kept: bool,
+    pub warnings: Vec<String>,
+}
+
+/// A file or empty folder to copy, relative to the game directory.
+struct SourceFile {
+    relative: PathBuf,
+    is_dir: bool,
+    size: u64,
+    sha256: String,
+}
+
+/// Copies the game directory `from` to `to`, verifies the copy and calls
+/// `commit` (which saves the new `game_dir`). Only then is `from` removed,
+/// unless `keep_source` is set.
+///
+/// `to` must not exist or be empty. If anything fails before `commit`
+/// succeeds, whatever was created at `to` is removed and `from` is untouched.
+/// The `MoveGuard` from `begin` keeps launches and restores out meanwhile.
+pub fn move_game_dir(
+    _guard: &MoveGuard,
+    from: &Path,
+    to: &Path,
+    keep_source: bool,
+    commit: impl FnOnce() -> Result<(), String>,
+    mut on_progress: impl FnMut(&MoveProgress),
+) -> Result<MoveOutcome, String> {
+    check_target(from, to)?;
+
+    if GameLock::read(from).is_some_and(|lock| lock.is_alive()) {
+        return Err("Close the game before moving its directory.".to_string());
+    }
+
+    // Nothing to move yet: only the setting changes
+    if !from.exists() {
+        commit()?;
+        info!("Game directory set to {:?} (the old one did not exist)", to);
+        return Ok(MoveOutcome {
+            game_dir: to.to_path_buf(),
+            files: 0,
+            bytes: 0,
+            source_kept: false,
+            warnings: Vec::new(),
+        });
+    }
+
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    let mut warnings = Vec::new();
+    collect(from, Path::new(""), &mut files, &mut links)
+        .map_err(|e| format!("Could not read {}: {}", from.display(), e))?;
+    // A link cannot be copied as one everywhere, and removing the old folder
+    // would delete it: a linked `mods` or `saves` would be lost
+    if let Some(link) = links.first() {
+        let more = match links.len() {
+            1 => String::new(),
+            n => format!(" (and {} more)", n - 1),
+        };
+        return Err(format!(
+            "{} is a link{}. Move the folder it points to into the game directory, or remove the link, before moving.",
+            link.display(),
+            more
+        ));
+    }
+    let bytes_total: u64 = files.iter().map(|f| f.size).sum();
+
+    let needed = bytes_total + SPACE_MARGIN;
+    if let Some(free) = existing_ancestor(to).and_then(|dir| available_space(&dir)) {
+        if free < needed {
+            return Err(format!(
+                "The new location needs {} MB free, but only {} MB are available.",
+                needed / (1024 * 1024),
+                free / (1024 * 1024)
+            ));
+        }
+    }
+
+    let created_dir = !to.exists();
+    let mut progress = MoveProgress {
+        phase: MovePhase::Copying,
+        files_done: 0,
+        files_total: files.len(),
+        bytes_done: 0,
+        bytes_total,
+    };
+    on_progress(&progress);
+
+    let result = copy_all(from, to, &mut files, &mut progress, &mut on_progress)
+        .and_then(|_| verify_all(to, &files, &mut progress, &mut on_progress))
+        .and_then(|_| commit());
+    if let Err(e) = result {
+        error!("Moving {:?} to {:?} failed: {}", from, to, e);
+        progress.phase = MovePhase::RollingBack;
+        on_progress(&progress);
+        roll_back(to, created_dir);
+        return Err(format!("{} Nothing was changed.", e));
+    }
+    info!("Copied {} file(s), {} bytes from {:?} to {:?}", files.len(), bytes_total, from, to);
+
+    let mut source_kept = keep_source;
+    if !keep_source {
+        progress.phase = MovePhase::RemovingSource;
+        on_progress(&progress);
+        if let Err(e) = fs::remove_dir_all(from) {
+            warn!("Could not remove the old game directory {:?}: {}", from, e);
+            warnings.push(format!("Some files stayed in {}: {}", from.display(), e));
+            source_kept = true;
+        }
+    }
+
+    Ok(MoveOutcome {
+        game_dir: to.to_path_buf(),
+        files: files.len(),
+        bytes: bytes_total,
+        source_kept,
+        warnings,
+    })
+}
+
+/// Refuses targets that would lose or nest the game files.
+fn check_target(from: &Path, to: &Path) -> Result<(), String> {
+    if !to.is_absolute() || to.components().any(|c| c == Component::ParentDir) {
+        return Err("Choose an absolute folder for the game.".to_string());
+    }
+    let from = fs::canonicalize(from).unwrap_or_else(|_| from.to_path_buf());
+    let to_real = existing_ancestor(to)
+        .and_then(|dir| fs::canonicalize(&dir).ok().map(|real| real.join(to.strip_prefix(&dir).unwrap_or(Path::new("")))))
+        .unwrap_or_else(|| to.to_path_buf());
+    if to_real == from {
+        return Err("The game is already in that folder.".to_string());
+    }
+    if to_real.starts_with(&from) || from.starts_with(&to_real) {
+        return Err("The new folder cannot be inside the current one, or the other way round.".to_string());
+    }
+    if to.is_file() {
+        return Err(format!("{} is a file.", to.display()));
+    }
+    if to.is_dir() && fs::read_dir(to).map_err(|e| e.to_string())?.next().is_some() {
+        return Err(format!("{} is not empty. Choose an empty or new folder.", to.display()));
+    }
+    Ok(())
+}
+
+/// Regular files and empty folders under `root.join(relative)`, without the
+/// game lock. Symlinks and junctions go to `links`.
+fn collect(root: &Path, relative: &Path, out: &mut Vec<SourceFile>, links: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut empty = true;
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        empty = false;
+        let path = relative.join(entry.file_name());
+        let meta = fs::symlink_metadata(entry.path())?;
+        if meta.is_dir() {
+            collect(root, &path, out, links)?;
+        } else if meta.is_file() {
+            if path.as_os_str() != LOCK_FILE {
+                out.push(SourceFile { relative: path, is_dir: false, size: meta.len(), sha256: String::new() });
+            }
+        } else {
+            links.push(path);
+        }
+    }
+    if empty && !relative.as_os_str().is_empty() {
+        out.push(SourceFile { relative: relative.to_path_buf(), is_dir: true, size: 0, sha256: String::new() });
+    }
+    Ok(())
+}
+
+fn copy_all(
+    from: &Path,
+    to: &Path,
+    files: &mut [SourceFile],
+    progress: &mut MoveProgress,
+    on_progress: &mut impl FnMut(&MoveProgress),
+) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Could not create {}: {}", to.display(), e))?;
+    let mut last_report = Instant::now();
+    for file in files.iter_mut() {
+        let source = from.join(&file.relative);
+        let target = to.join(&file.relative);
+        if file.is_dir {
+            fs::create_dir_all(&target).map_err(|e| format!("Could not create {}: {}", target.display(), e))?;
+        } else {
+            file.sha256 = copy_file(&source, &target, |n| {
+                progress.bytes_done += n;
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    on_progress(progress);
+                }
+            })
+            .map_err(|e| format!("Could not copy {}: {}", file.relative.display(), e))?;
+        }
+        progress.files_done += 1;
+    }
+    Ok(())
+}
+
+/// Copies one file, keeping its modification time, and returns the SHA-256
+/// of what was read.
+fn copy_file(source: &Path, target: &Path, mut on_bytes: impl FnMut(u64)) -> io::Result<String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let input = File::open(source)?;
+    let modified = input.metadata()?.modified().ok();
+    let mut reader = BufReader::new(input);
+    let mut output = BufWriter::new(File::create(target)?);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        output.write_all(&buf[..n])?;
+        on_bytes(n as u64);
+    }
+    let file = output.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    if let Some(modified) = modified {
+        let _ = file.set_modified(modified);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Reads every copied file back and compares it with the source's hash.
+fn verify_all(
+    to: &Path,
+    files: &[SourceFile],
+    progress: &mut MoveProgress,
+    on_progress: &mut impl FnMut(&MoveProgress),
+) -> Result<(), String> {
+    progress.phase = MovePhase::Verifying;
+    progress.files_done = 0;
+    on_progress(progress);
+    let mut last_report = Instant::now();
+    for file in files {
+        if !file.is_dir {
+            let path = to.join(&file.relative);
+            let copied = hash_file(&path).map_err(|e| format!("Could not check {}: {}", file.relative.display(), e))?;
+            if copied != file.sha256 {
+                return Err(format!("The copy of {} does not match the original.", file.relative.display()));
+            }
+        }
+        progress.files_done += 1;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            on_progress(progress);
+        }
+    }
+    on_progress(progress);
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut BufReader::new(File::open(path)?), &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Removes what a failed move created. `to` was empty or missing before.
+fn roll_back(to: &Path, created_dir: bool) {
+    let result = if created_dir {
+        fs::remove_dir_all(to)
+    } else {
+        fs::read_dir(to).and_then(|entries| {
+            for entry in entries {
+                let path = entry?.path();
+                if path.is_dir() {
+                    fs::remove_dir_all(&path)?;
+                } else {
+                    fs::remove_file(&path)?;
+                }
+            }
+            Ok(())
+        })
+    };
+    match result {
+        Ok(_) => info!("Rolled back the partial copy in {:?}", to),
+        Err(e) => warn!("Could not clean up the partial copy in {:?}: {}", to, e),
+    }
+}
+
+/// `path` itself or its closest parent that exists, for the space check.
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.exists()).map(Path::to_path_buf)
+}
+
+// ---------------------------------------------------------------------------
+// Platform helpers
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut()) };
+    (ok != 0).then_some(free)
+}
+
+/// Without a way to ask, the copy itself reports a full disk and is rolled back.
+#[cfg(not(any(target_os = "linux", windows)))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
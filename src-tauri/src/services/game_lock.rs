@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::services::game_session::GameSession;
+
+/// Name of the lock file inside `game_dir`.
+pub(crate) const LOCK_FILE: &str = "launcher-game.lock";
+
+/// Written to `game_dir` while a game launched by the launcher is running.
+///
+/// The launcher keeps the game state in memory, so after a crash or restart it
+/// would report `Idle` while Minecraft still runs. The lock lets a new launcher
+/// find that game again, refuse a second launch into the same directory and
+/// keep offering stop and metrics for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameLock {
+    pub pid: u32,
+    pub session_id: String,
+    pub username: String,
+    /// Unix timestamp of the launch.
+    pub started_at: i64,
+    /// Start time of the process (clock ticks since boot on Linux, creation
+    /// `FILETIME` on Windows), used to tell the game apart from an unrelated
+    /// process that reused its PID. `None` where it cannot be read.
+    #[serde(default)]
+    pub process_start: Option<u64>,
+}
+
+impl GameLock {
+    pub fn new(pid: u32, session: &GameSession) -> Self {
+        Self {
+            pid,
+            session_id: session.id().to_string(),
+            username: session.username().to_string(),
+            started_at: chrono::Utc::now().timestamp(),
+            process_start: process_start(pid),
+        }
+    }
+
+    fn path(game_dir: &Path) -> PathBuf {
+        game_dir.join(LOCK_FILE)
+    }
+
+    pub fn write(&self, game_dir: &Path) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(Self::path(game_dir), json).map_err(|e| e.to_string()));
+        match result {
+            Ok(_) => info!("Game lock written for PID {} in {:?}", self.pid, game_dir),
+            Err(e) => warn!("Could not write the game lock in {:?}: {}", game_dir, e),
+        }
+    }
+
+    /// The lock left in `game_dir`, if any.
+    pub fn read(game_dir: &Path) -> Option<Self> {
+        let data = std::fs::read_to_string(Self::path(game_dir)).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                warn!("Ignoring unreadable game lock in {:?}: {}", game_dir, e);
+                None
+            }
+        }
+    }
+
+    pub fn remove(game_dir: &Path) {
+        let path = Self::path(game_dir);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Could not remove the game lock {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Whether the game may still be running: its PID exists and its start
+    /// time, when known, matches. Enough to refuse a second launch.
+    pub fn is_alive(&self) -> bool {
+        if !process_exists(self.pid) {
+            return false;
+        }
+        match (self.process_start, process_start(self.pid)) {
+            (Some(recorded), Some(current)) => recorded == current,
+            _ => true,
+        }
+    }
+
+    /// Whether the PID is known to still be the game, because its start time
+    /// was recorded and matches. Required before adopting or killing it, so an
+    /// unrelated process that reused the PID is never touched.
+    pub fn is_verified(&self) -> bool {
+        process_exists(self.pid)
+            && self.process_start.is_some()
+            && self.process_start == process_start(self.pid)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Platform helpers
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+fn process_start(pid: u32) -> Option<u64> {
+    crate::services::process_metrics::proc_stat(pid).map(|s| s.start_ticks)
+}
+
+#[cfg(windows)]
+fn process_start(pid: u32) -> Option<u64> {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::Threading::{GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    let empty = || FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+    let (mut created, mut exited, mut kernel, mut user) = (empty(), empty(), empty(), empty());
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let ok = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) != 0;
+        CloseHandle(handle);
+        ok.then(|| (u64::from(created.dwHighDateTime) << 32) | u64::from(created.dwLowDateTime))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_start(_pid: u32) -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn process_exists(pid: u32) -> bool {
+    Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+#[cfg(windows)]
+fn process_exists(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_exists(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Kills a game the launcher has no `Child` handle for, including the
+/// Minecraft client started by the game manager.
+#[cfg(target_os = "linux")]
+pub fn kill_process_tree(pid: u32) {
+    for p in crate::services::process_metrics::process_tree(pid).into_iter().rev() {
+        unsafe {
+            libc::kill(p as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(windows)]
+pub fn kill_process_tree(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status();
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn kill_process_tree(pid: u32) {
+    let _ = std::process::Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .status();
+}
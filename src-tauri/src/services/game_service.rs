@@ -2,12 +2,27 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
 use log::{info, warn, error};
-use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WebviewWindowBuilder, WebviewUrl};
+use tauri::{Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WebviewWindowBuilder, WebviewUrl};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::sync::oneshot;
 
+use crate::core::paths;
 use crate::models::options::LauncherOptions;
+use crate::services::backup_service::{self, BackupIo, BackupOutcome, BackupTrigger};
+use crate::services::diagnosis_service::DiagnosisEngine;
+use crate::services::game_dir_mover;
+use crate::services::event_sink::EventSink;
+use crate::services::game_lock::{self, GameLock};
+use crate::services::game_session::GameSession;
+use crate::services::hook_service::{self, HookContext, HookKind};
+use crate::services::log_parser::LogParser;
 use crate::services::options_repository::OptionsRepository;
+use crate::services::process_metrics::{GameMetrics, MetricsSampler};
+use crate::services::process_priority;
+use crate::services::redaction::REDACTED;
+
+/// How long a log record may wait for continuation lines before it is emitted.
+const RECORD_FLUSH_DELAY: std::time::Duration = std::time::Duration::from_millis(250);
 
 /// Saved window state: (fullscreen, maximized, size, position)
 type SavedWindowState = Option<(bool, bool, Option<PhysicalSize<u32>>, Option<PhysicalPosition<i32>>)>;
@@ -42,10 +57,16 @@ async fn restore_window(w: &WebviewWindow, state: &SavedWindowState) {
 // Public types
 // ---------------------------------------------------------------------------
 
+/// The only launch profile for now: the server's modpack with the saved options.
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GameState {
     Idle,
+    /// Between the launch request and the game process starting: backups,
+    /// hooks and the mods list run here.
+    Preparing,
     Downloading,
     Running,
 }
@@ -55,15 +76,60 @@ pub enum GameState {
 pub struct GameLogLine {
     pub line: String,
     pub is_error: bool,
+    /// The line is game output, which is also sent as `game-log-record`.
+    pub from_game: bool,
+}
+
+/// How the game manager will authenticate the player.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum AuthMode {
+    Offline { username: String },
+    /// A stored Microsoft token is passed to the game manager.
+    Microsoft,
+    /// No stored token; the game manager opens the browser to log in.
+    MicrosoftLogin,
+}
+
+/// Everything `launch` would use, resolved without starting anything.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPreview {
+    pub profile: String,
+    /// The `java` found on `PATH`, or `None` when there is none.
+    pub java: Option<PathBuf>,
+    pub jar_path: PathBuf,
+    /// False when the JAR would be downloaded first.
+    pub jar_present: bool,
+    pub game_dir: PathBuf,
+    /// `MODS_JSON_URL`; `None` means the game launches without mods.
+    pub mods_manifest_url: Option<String>,
+    pub mods_manifest_path: Option<PathBuf>,
+    pub auth: AuthMode,
+    pub max_ram_mb: u32,
+    pub jvm_flags: Vec<String>,
+    /// Arguments passed to `java`, with secrets redacted.
+    pub args: Vec<String>,
+    /// The full command, quoted for the platform's shell.
+    pub command_line: String,
 }
 
 // ---------------------------------------------------------------------------
 // GameManager — Tauri managed state
 // ---------------------------------------------------------------------------
 
+/// The game process of the current session.
+#[derive(Clone)]
+pub struct RunningGame {
+    pub pid: u32,
+    pub session: Arc<GameSession>,
+}
+
 pub struct GameManager {
     state: Arc<Mutex<GameState>>,
     kill_tx: Arc<tokio::sync::Mutex<Option<oneshot::Sender<()>>>>,
+    running: Arc<Mutex<Option<RunningGame>>>,
+    metrics: Arc<Mutex<Option<GameMetrics>>>,
 }
 
 impl GameManager {
@@ -71,6 +137,8 @@ impl GameManager {
         Self {
             state: Arc::new(Mutex::new(GameState::Idle)),
             kill_tx: Arc::new(tokio::sync::Mutex::new(None)),
+            running: Arc::new(Mutex::new(None)),
+            metrics: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -78,6 +146,26 @@ impl GameManager {
         self.state.lock().unwrap().clone()
     }
 
+    /// Runs `claim` while the game is idle, holding the state lock so no launch
+    /// can begin in between. Used to start work that must not overlap a game.
+    pub fn while_idle<T>(&self, claim: impl FnOnce(&mut GameState) -> Result<T, String>) -> Result<T, String> {
+        let mut state = self.state.lock().unwrap();
+        if *state != GameState::Idle {
+            return Err("The game is already running or being prepared.".to_string());
+        }
+        claim(&mut state)
+    }
+
+    /// The process of the running game, if any.
+    pub fn get_running(&self) -> Option<RunningGame> {
+        self.running.lock().unwrap().clone()
+    }
+
+    /// Latest resource sample of the running game, if any.
+    pub fn get_metrics(&self) -> Option<GameMetrics> {
+        self.metrics.lock().unwrap().clone()
+    }
+
     pub async fn kill(&self) {
         let mut guard = self.kill_tx.lock().await;
         if let Some(tx) = guard.take() {
@@ -85,6 +173,15 @@ impl GameManager {
         }
     }
 
+    /// Fails for profiles other than the default one, which is all the launcher supports.
+    pub fn check_profile(profile: &str) -> Result<(), String> {
+        if profile == DEFAULT_PROFILE {
+            Ok(())
+        } else {
+            Err(format!("Unknown profile '{}'. Available profiles: {}.", profile, DEFAULT_PROFILE))
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Microsoft auth state check
     // -----------------------------------------------------------------------
@@ -101,7 +198,7 @@ impl GameManager {
     /// Try to download the mods JSON from `MODS_JSON_URL`.
     /// Returns the local path if successful, or `None` if the URL is not set
     /// or the download fails (non-fatal — just means we launch without mods).
-    async fn fetch_mods_json(launcher_opts: &LauncherOptions, app: &AppHandle) -> Option<PathBuf> {
+    async fn fetch_mods_json(launcher_opts: &LauncherOptions, events: &EventSink) -> Option<PathBuf> {
         let url = match std::env::var("MODS_JSON_URL") {
             Ok(u) if !u.is_empty() => u,
             _ => return None,
@@ -119,9 +216,10 @@ impl GameManager {
 
         if !response.status().is_success() {
             warn!("Mods JSON URL returned {}, launching without mods", response.status());
-            let _ = app.emit("game-log", GameLogLine {
+            events.emit("game-log", GameLogLine {
                 line: format!("Mods list not available ({}), launching without mods.", response.status()),
                 is_error: false,
+                from_game: false,
             });
             return None;
         }
@@ -134,11 +232,7 @@ impl GameManager {
             }
         };
 
-        let mods_path = launcher_opts
-            .game_dir
-            .clone()
-            .unwrap_or_else(|| PathBuf::from(".permadeath"))
-            .join("mods-list.json");
+        let mods_path = Self::get_mods_json_path(launcher_opts);
 
         if let Some(parent) = mods_path.parent() {
             let _ = std::fs::create_dir_all(parent);
@@ -177,8 +271,7 @@ impl GameManager {
             .to_string();
 
         let max_ram = game_opts.get_max_ram();
-        // get_vm_flags() appends the GC flag at the end; UnlockExperimentalVMOptions
-        // is now the first element of BASE_VM_FLAGS so the order is always correct.
+        // get_vm_flags() puts the unlock switches first and the GC flag last
         let jvm_flags_str = game_opts.get_vm_flags().join(" ");
 
         let mut args: Vec<String> = vec![
@@ -216,11 +309,72 @@ impl GameManager {
             info!("--microsoft mode enabled (no stored token, CLI will open browser)");
         }
 
-        info!("Built CLI arguments: {:?}", args);
+        info!("Built CLI arguments: {:?}", redact_args(&args));
 
         args
     }
 
+    // -----------------------------------------------------------------------
+    // Preview
+    // -----------------------------------------------------------------------
+
+    /// Resolves the command line `launch` would run, without downloading,
+    /// writing or starting anything.
+    pub fn preview(username: &str, use_microsoft: bool, profile: &str) -> Result<LaunchPreview, String> {
+        Self::check_profile(profile)?;
+
+        let launcher_opts = OptionsRepository::load_launcher_options();
+        let game_opts = OptionsRepository::load_game_options(&launcher_opts);
+        let jar_path = Self::get_jar_path();
+
+        let mods_manifest_url = std::env::var("MODS_JSON_URL").ok().filter(|u| !u.is_empty());
+        let mods_manifest_path = mods_manifest_url
+            .as_ref()
+            .map(|_| Self::get_mods_json_path(&launcher_opts));
+
+        let auth = if !use_microsoft {
+            AuthMode::Offline { username: username.to_string() }
+        } else if launcher_opts.microsoft_token.is_some() {
+            AuthMode::Microsoft
+        } else {
+            AuthMode::MicrosoftLogin
+        };
+
+        let args = redact_args(&Self::build_args(
+            &launcher_opts,
+            username,
+            &jar_path,
+            use_microsoft,
+            mods_manifest_path.as_deref(),
+        ));
+        let java = find_on_path("java");
+
+        let java_display = java
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "java".to_string());
+        let command_line = std::iter::once(java_display.as_str())
+            .chain(args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(LaunchPreview {
+            profile: profile.to_string(),
+            java,
+            jar_present: jar_path.exists(),
+            jar_path,
+            game_dir: Self::get_game_dir(&launcher_opts),
+            mods_manifest_url,
+            mods_manifest_path,
+            auth,
+            max_ram_mb: game_opts.get_max_ram(),
+            jvm_flags: game_opts.get_vm_flags(),
+            args,
+            command_line,
+        })
+    }
+
     // -----------------------------------------------------------------------
     // Launch
     // -----------------------------------------------------------------------
@@ -229,36 +383,76 @@ impl GameManager {
         &self,
         username: String,
         use_microsoft: bool,
-        app: AppHandle,
+        events: EventSink,
     ) -> Result<(), String> {
-        if self.get_state() != GameState::Idle {
-            return Err("The game is already running or downloading.".to_string());
+        self.while_idle(|state| {
+            if game_dir_mover::in_progress() {
+                return Err("The game directory is being moved. Wait for it to finish.".to_string());
+            }
+            if backup_service::restore_in_progress() {
+                return Err("A backup is being restored. Wait for it to finish.".to_string());
+            }
+            *state = GameState::Preparing;
+            Ok(())
+        })?;
+        events.emit("game-state", GameState::Preparing);
+
+        let result = self.start(username, use_microsoft, &events).await;
+        if result.is_err() {
+            *self.state.lock().unwrap() = GameState::Idle;
+            events.emit("game-state", GameState::Idle);
         }
+        result
+    }
 
+    /// The launch itself, with the state already set to `Preparing`. `launch`
+    /// puts it back to `Idle` when this fails.
+    async fn start(
+        &self,
+        username: String,
+        use_microsoft: bool,
+        events: &EventSink,
+    ) -> Result<(), String> {
         let launcher_opts = OptionsRepository::load_launcher_options();
-        let jar_path = Self::get_jar_path(&launcher_opts);
+        let jar_path = Self::get_jar_path();
+        let game_dir = Self::get_game_dir(&launcher_opts);
+
+        // A game started by an earlier launcher process may still be running
+        if let Some(lock) = GameLock::read(&game_dir) {
+            if lock.is_alive() {
+                return Err(format!(
+                    "A game launched earlier is still running (PID {}). Stop it before launching again.",
+                    lock.pid
+                ));
+            }
+            GameLock::remove(&game_dir);
+        }
+
+        let game_opts = OptionsRepository::load_game_options(&launcher_opts);
+        let priority = game_opts.get_process_priority().clone();
+        process_priority::validate(&priority)?;
 
         // Download CLI JAR if missing
         if !jar_path.exists() {
             *self.state.lock().unwrap() = GameState::Downloading;
-            let _ = app.emit("game-state", GameState::Downloading);
-            let _ = app.emit("game-log", GameLogLine {
+            events.emit("game-state", GameState::Downloading);
+            events.emit("game-log", GameLogLine {
                 line: "Game manager not found. Downloading...".to_string(),
                 is_error: false,
+                from_game: false,
             });
-            if let Err(e) = Self::download_jar(&jar_path).await {
-                *self.state.lock().unwrap() = GameState::Idle;
-                let _ = app.emit("game-state", GameState::Idle);
-                return Err(e);
-            }
-            let _ = app.emit("game-log", GameLogLine {
+            Self::download_jar(&jar_path).await?;
+            events.emit("game-log", GameLogLine {
                 line: "Download complete. Preparing launch...".to_string(),
                 is_error: false,
+                from_game: false,
             });
+            *self.state.lock().unwrap() = GameState::Preparing;
+            events.emit("game-state", GameState::Preparing);
         }
 
         // Fetch mods list (non-fatal if unavailable)
-        let mods_path = Self::fetch_mods_json(&launcher_opts, &app).await;
+        let mods_path = Self::fetch_mods_json(&launcher_opts, events).await;
 
         let args = Self::build_args(
             &launcher_opts,
@@ -268,26 +462,87 @@ impl GameManager {
             mods_path.as_deref(),
         );
 
-        info!("Launching game: java {:?}", args);
+        let _ = std::fs::create_dir_all(&game_dir);
+
+        Self::run_backup(&launcher_opts, &game_dir, BackupTrigger::PreLaunch, events).await;
+
+        let session = Arc::new(GameSession::start(&launcher_opts, &username));
+        let diagnosis = Arc::new(Mutex::new(
+            DiagnosisEngine::load(&launcher_opts, game_opts.get_max_ram()),
+        ));
+        DiagnosisEngine::refresh(Arc::clone(&diagnosis), &launcher_opts);
+
+        // Pre-launch hook failures are reported but don't block the launch
+        if let Some(hook) = &launcher_opts.hooks.pre_launch {
+            let ctx = HookContext { game_dir: &game_dir, exit_code: None, duration: None };
+            if let Err(e) = hook_service::run_hook(HookKind::PreLaunch, hook, &ctx, &session).await {
+                events.emit("game-log", GameLogLine { line: e, is_error: true, from_game: false });
+            }
+        }
+
+        info!("Launching game: java {:?}", redact_args(&args));
 
-        let mut child = tokio::process::Command::new("java")
+        let mut command = tokio::process::Command::new("java");
+        command
             .args(&args)
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            .stderr(Stdio::piped());
+        process_priority::apply(&mut command, &priority);
+
+        let spawned_at = std::time::SystemTime::now();
+        let mut child = command
             .spawn()
             .map_err(|e| format!("Failed to start the game: {e}. Is Java 21 installed?"))?;
 
+        let pid = child.id().unwrap_or_default();
+        GameLock::new(pid, &session).write(&game_dir);
+        *self.running.lock().unwrap() = Some(RunningGame { pid, session: Arc::clone(&session) });
         *self.state.lock().unwrap() = GameState::Running;
-        let _ = app.emit("game-state", GameState::Running);
+        events.emit("game-state", GameState::Running);
+
+        if launcher_opts.metrics_interval_secs > 0 {
+            self.spawn_metrics_task(pid, game_opts.get_max_ram(), launcher_opts.metrics_interval_secs, events.clone());
+        }
+        if launcher_opts.periodic_backup.enabled {
+            self.spawn_backup_task(pid, launcher_opts.clone(), game_dir.clone(), events.clone());
+        }
 
         // Pipe stdout
         let stdout = child.stdout.take().unwrap();
-        let app_out = app.clone();
+        let events_out = events.clone();
+        let session_out = Arc::clone(&session);
+        let diagnosis_out = Arc::clone(&diagnosis);
         tokio::spawn(async move {
             let mut lines = BufReader::new(stdout).lines();
+            let mut parser = LogParser::new(false);
             let mut capturing_auth = false;
-            while let Ok(Some(line)) = lines.next_line().await {
-                info!("[game] {}", line);
+            loop {
+                let line = match tokio::time::timeout(RECORD_FLUSH_DELAY, lines.next_line()).await {
+                    Ok(Ok(Some(line))) => line,
+                    Ok(_) => break,
+                    Err(_) => {
+                        if let Some(record) = parser.flush() {
+                            events_out.emit("game-log-record", record);
+                        }
+                        continue;
+                    }
+                };
+                // The auth block carries the refresh token, so none of it is
+                // logged, stored in the session or sent as an event
+                let marker = matches!(line.trim(), "---AUTH_DATA---" | "---END_AUTH_DATA---");
+                let hidden = capturing_auth || marker;
+
+                if !hidden {
+                    info!("[game] {}", line);
+                    session_out.append(&line);
+                    let found = diagnosis_out.lock().unwrap().check_line(&line);
+                    for d in found {
+                        events_out.emit("game-diagnosis", d);
+                    }
+                    if let Some(record) = parser.push(&line) {
+                        events_out.emit("game-log-record", record);
+                    }
+                }
 
                 // Token capturing logic
                 if line.trim() == "---AUTH_DATA---" {
@@ -300,22 +555,51 @@ impl GameManager {
                         info!("Microsoft token captured from game output.");
                         let mut opts = OptionsRepository::load_launcher_options();
                         opts.microsoft_token = Some(token);
-                        OptionsRepository::save_launcher_options(&opts);
+                        let _ = OptionsRepository::save_launcher_options(&opts);
                     }
                 }
 
-                let _ = app_out.emit("game-log", GameLogLine { line, is_error: false });
+                if !hidden {
+                    events_out.emit("game-log", GameLogLine { line, is_error: false, from_game: true });
+                }
+            }
+            if let Some(record) = parser.flush() {
+                events_out.emit("game-log-record", record);
             }
         });
 
         // Pipe stderr
         let stderr = child.stderr.take().unwrap();
-        let app_err = app.clone();
+        let events_err = events.clone();
+        let session_err = Arc::clone(&session);
+        let diagnosis_err = Arc::clone(&diagnosis);
         tokio::spawn(async move {
             let mut lines = BufReader::new(stderr).lines();
-            while let Ok(Some(line)) = lines.next_line().await {
+            let mut parser = LogParser::new(true);
+            loop {
+                let line = match tokio::time::timeout(RECORD_FLUSH_DELAY, lines.next_line()).await {
+                    Ok(Ok(Some(line))) => line,
+                    Ok(_) => break,
+                    Err(_) => {
+                        if let Some(record) = parser.flush() {
+                            events_err.emit("game-log-record", record);
+                        }
+                        continue;
+                    }
+                };
                 error!("[game-err] {}", line);
-                let _ = app_err.emit("game-log", GameLogLine { line, is_error: true });
+                session_err.append(&line);
+                let found = diagnosis_err.lock().unwrap().check_line(&line);
+                for d in found {
+                    events_err.emit("game-diagnosis", d);
+                }
+                if let Some(record) = parser.push(&line) {
+                    events_err.emit("game-log-record", record);
+                }
+                events_err.emit("game-log", GameLogLine { line, is_error: true, from_game: true });
+            }
+            if let Some(record) = parser.flush() {
+                events_err.emit("game-log-record", record);
             }
         });
 
@@ -323,6 +607,8 @@ impl GameManager {
         let (kill_tx, kill_rx) = oneshot::channel::<()>();
         *self.kill_tx.lock().await = Some(kill_tx);
 
+        // Windows are only managed when there is a UI (not in headless mode)
+        let ui             = events.app().cloned();
         let close_launcher = launcher_opts.close_on_launch;
         let show_debug     = launcher_opts.debug_console;
 
@@ -331,105 +617,376 @@ impl GameManager {
         // close + !debug → hide main window, no console
         // !close + *     → keep main window open (console visible if debug is on)
         // Save the main window state before hiding so we can restore it exactly.
-        let saved_window_state = if close_launcher {
-            if show_debug {
-                // Open a dedicated console window
-                if let Err(e) = WebviewWindowBuilder::new(
-                    &app,
-                    "console",
-                    WebviewUrl::App("index.html#/console".into()),
-                )
-                .title("PERMADEATHSMP — Console")
-                .inner_size(1000.0, 600.0)
-                .min_inner_size(600.0, 400.0)
-                .resizable(true)
-                .decorations(true)
-                .center()
-                .build()
-                {
-                    error!("Failed to open console window: {}", e);
-                } else {
-                    // Give it a tiny bit of time to initialize and then send a confirmation log
-                    let app_c = app.clone();
-                    tokio::spawn(async move {
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        let _ = app_c.emit_to("console", "game-log", GameLogLine {
-                            line: "=== Console window initialized. Waiting for logs... ===".into(),
-                            is_error: false,
+        let saved_window_state = match (&ui, close_launcher) {
+            (Some(app), true) => {
+                if show_debug {
+                    // Open a dedicated console window
+                    if let Err(e) = WebviewWindowBuilder::new(
+                        app,
+                        "console",
+                        WebviewUrl::App("index.html#/console".into()),
+                    )
+                    .title("PERMADEATHSMP — Console")
+                    .inner_size(1000.0, 600.0)
+                    .min_inner_size(600.0, 400.0)
+                    .resizable(true)
+                    .decorations(true)
+                    .center()
+                    .build()
+                    {
+                        error!("Failed to open console window: {}", e);
+                    } else {
+                        // Give it a tiny bit of time to initialize and then send a confirmation log
+                        let app_c = app.clone();
+                        tokio::spawn(async move {
+                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                            let _ = app_c.emit_to("console", "game-log", GameLogLine {
+                                line: "=== Console window initialized. Waiting for logs... ===".into(),
+                                is_error: false,
+                                from_game: false,
+                            });
                         });
-                    });
+                    }
+                }
+                if let Some(win) = app.get_webview_window("main") {
+                    let fs = win.is_fullscreen().unwrap_or(false);
+                    let maximized = win.is_maximized().unwrap_or(false);
+                    let size = win.inner_size().ok();
+                    let pos = win.outer_position().ok();
+                    info!("Saving window state before hide: fullscreen={}, maximized={}, size={:?}, pos={:?}", fs, maximized, size, pos);
+                    let _ = win.minimize();
+                    let _ = win.hide();
+                    Some((fs, maximized, size, pos))
+                } else {
+                    None
                 }
             }
-            if let Some(win) = app.get_webview_window("main") {
-                let fs = win.is_fullscreen().unwrap_or(false);
-                let maximized = win.is_maximized().unwrap_or(false);
-                let size = win.inner_size().ok();
-                let pos = win.outer_position().ok();
-                info!("Saving window state before hide: fullscreen={}, maximized={}, size={:?}, pos={:?}", fs, maximized, size, pos);
-                let _ = win.minimize();
-                let _ = win.hide();
-                Some((fs, maximized, size, pos))
-            } else {
-                None
-            }
-        } else {
-            None
+            _ => None,
         };
 
         // Wait task
         let state_arc   = Arc::clone(&self.state);
         let kill_tx_arc = Arc::clone(&self.kill_tx);
+        let running_arc = Arc::clone(&self.running);
+        let metrics_arc = Arc::clone(&self.metrics);
+        let events      = events.clone();
+
+        let hooks = launcher_opts.hooks.clone();
 
         tokio::spawn(async move {
-            tokio::select! {
+            let (code, stopped_by_user) = tokio::select! {
                 result = child.wait() => {
                     let code = match result {
                         Ok(s)  => s.code().unwrap_or(-1),
                         Err(e) => { error!("Error waiting for game: {}", e); -1 }
                     };
                     info!("Game exited with code {}", code);
-                    *state_arc.lock().unwrap() = GameState::Idle;
-                    *kill_tx_arc.lock().await = None;
-                    let _ = app.emit("game-state", GameState::Idle);
-                    let _ = app.emit("game-exited", code);
-                    if close_launcher {
-                        if let Some(w) = app.get_webview_window("console") { let _ = w.close(); }
-                        if let Some(w) = app.get_webview_window("main") {
-                            restore_window(&w, &saved_window_state).await;
-                        }
-                    }
+                    (code, false)
                 },
                 _ = kill_rx => {
                     info!("Kill signal received — terminating game");
                     let _ = child.kill().await;
                     let _ = child.wait().await;
-                    *state_arc.lock().unwrap() = GameState::Idle;
-                    *kill_tx_arc.lock().await = None;
-                    let _ = app.emit("game-state", GameState::Idle);
-                    let _ = app.emit("game-exited", -1i32);
-                    if close_launcher {
-                        if let Some(w) = app.get_webview_window("console") { let _ = w.close(); }
-                        if let Some(w) = app.get_webview_window("main") {
-                            restore_window(&w, &saved_window_state).await;
+                    (-1, true)
+                }
+            };
+
+            session.append(&format!("=== Game exited with code {} ===", code));
+
+            let crashed = code != 0 && !stopped_by_user;
+            if crashed {
+                let found = diagnosis.lock().unwrap().check_crash_reports(&game_dir, spawned_at);
+                for d in found {
+                    events.emit("game-diagnosis", d);
+                }
+            }
+
+            // Hooks run before going back to Idle so a new launch can't race them
+            let ctx = HookContext {
+                game_dir: &game_dir,
+                exit_code: Some(code),
+                duration: Some(session.elapsed()),
+            };
+            if let Some(hook) = &hooks.post_exit {
+                let _ = hook_service::run_hook(HookKind::PostExit, hook, &ctx, &session).await;
+            }
+            if crashed {
+                if let Some(hook) = &hooks.on_crash {
+                    let _ = hook_service::run_hook(HookKind::OnCrash, hook, &ctx, &session).await;
+                }
+            }
+            Self::run_backup(&launcher_opts, &game_dir, BackupTrigger::PostExit, &events).await;
+
+            GameLock::remove(&game_dir);
+            *running_arc.lock().unwrap() = None;
+            *metrics_arc.lock().unwrap() = None;
+            *state_arc.lock().unwrap() = GameState::Idle;
+            *kill_tx_arc.lock().await = None;
+            events.emit("game-state", GameState::Idle);
+            events.emit("game-exited", code);
+            if let (Some(app), true) = (&ui, close_launcher) {
+                if let Some(w) = app.get_webview_window("console") { let _ = w.close(); }
+                if let Some(w) = app.get_webview_window("main") {
+                    restore_window(&w, &saved_window_state).await;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Reattach
+    // -----------------------------------------------------------------------
+
+    /// Picks up a game that kept running after the launcher that started it
+    /// exited, using the lock file in `game_dir`. The game's output went to the
+    /// old launcher and is lost, but it can still be stopped and monitored.
+    pub async fn reattach(&self, events: EventSink) {
+        if self.get_state() != GameState::Idle {
+            return;
+        }
+
+        let launcher_opts = OptionsRepository::load_launcher_options();
+        let game_dir = Self::get_game_dir(&launcher_opts);
+        let Some(lock) = GameLock::read(&game_dir) else { return };
+
+        if !lock.is_alive() {
+            info!("Removing stale game lock: PID {} is no longer running", lock.pid);
+            GameLock::remove(&game_dir);
+            return;
+        }
+        if !lock.is_verified() {
+            // The lock stays, so launches into this directory are still refused
+            warn!("Not reattaching to PID {}: it cannot be confirmed to be the game", lock.pid);
+            return;
+        }
+
+        info!("Reattaching to game PID {} from session {}", lock.pid, lock.session_id);
+
+        let running_for = (chrono::Utc::now().timestamp() - lock.started_at).max(0) as u64;
+        let session = Arc::new(GameSession::resume(
+            &launcher_opts,
+            &lock.session_id,
+            &lock.username,
+            std::time::Duration::from_secs(running_for),
+        ));
+        let notice = "=== Launcher restarted. The game is still running, but its output can no longer be shown. ===";
+        session.append(notice);
+
+        let pid = lock.pid;
+        *self.running.lock().unwrap() = Some(RunningGame { pid, session: Arc::clone(&session) });
+        *self.state.lock().unwrap() = GameState::Running;
+        events.emit("game-state", GameState::Running);
+        events.emit("game-log", GameLogLine { line: notice.to_string(), is_error: false, from_game: false });
+
+        if launcher_opts.metrics_interval_secs > 0 {
+            let max_ram = OptionsRepository::load_game_options(&launcher_opts).get_max_ram();
+            self.spawn_metrics_task(pid, max_ram, launcher_opts.metrics_interval_secs, events.clone());
+        }
+        if launcher_opts.periodic_backup.enabled {
+            self.spawn_backup_task(pid, launcher_opts.clone(), game_dir.clone(), events.clone());
+        }
+
+        let (kill_tx, mut kill_rx) = oneshot::channel::<()>();
+        *self.kill_tx.lock().await = Some(kill_tx);
+
+        let state_arc   = Arc::clone(&self.state);
+        let kill_tx_arc = Arc::clone(&self.kill_tx);
+        let running_arc = Arc::clone(&self.running);
+        let metrics_arc = Arc::clone(&self.metrics);
+        let hooks = launcher_opts.hooks.clone();
+
+        // Without a Child handle the exit is noticed by polling and its code is unknown
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => {
+                        if !lock.is_alive() {
+                            info!("Reattached game PID {} exited", pid);
+                            break;
+                        }
+                    }
+                    _ = &mut kill_rx => {
+                        if lock.is_verified() {
+                            info!("Kill signal received — terminating reattached game");
+                            game_lock::kill_process_tree(pid);
+                        } else {
+                            warn!("Not killing PID {}: it is no longer the game", pid);
                         }
+                        break;
                     }
                 }
             }
+
+            session.append("=== Game exited ===");
+
+            let ctx = HookContext {
+                game_dir: &game_dir,
+                exit_code: None,
+                duration: Some(session.elapsed()),
+            };
+            if let Some(hook) = &hooks.post_exit {
+                let _ = hook_service::run_hook(HookKind::PostExit, hook, &ctx, &session).await;
+            }
+            Self::run_backup(&launcher_opts, &game_dir, BackupTrigger::PostExit, &events).await;
+
+            GameLock::remove(&game_dir);
+            *running_arc.lock().unwrap() = None;
+            *metrics_arc.lock().unwrap() = None;
+            *state_arc.lock().unwrap() = GameState::Idle;
+            *kill_tx_arc.lock().await = None;
+            events.emit("game-state", GameState::Idle);
+            events.emit("game-exited", -1);
         });
+    }
 
-        Ok(())
+    // -----------------------------------------------------------------------
+    // Backups
+    // -----------------------------------------------------------------------
+
+    /// Takes an automatic backup when `automatic_backup` is on. Failures are
+    /// reported in the game log but never stop a launch.
+    async fn run_backup(launcher_opts: &LauncherOptions, game_dir: &Path, trigger: BackupTrigger, events: &EventSink) {
+        if !launcher_opts.automatic_backup {
+            return;
+        }
+        let sources = backup_service::BACKUP_SOURCES.iter().map(|s| s.to_string()).collect();
+        Self::take_backup(launcher_opts, game_dir, trigger, sources, None, events).await;
+    }
+
+    /// Writes a backup on a blocking thread, reporting progress as
+    /// `backup-progress` and the result as `backup-finished` and in the game log.
+    async fn take_backup(
+        launcher_opts: &LauncherOptions,
+        game_dir: &Path,
+        trigger: BackupTrigger,
+        sources: Vec<String>,
+        max_io_mb_per_sec: Option<u32>,
+        events: &EventSink,
+    ) {
+        events.emit("game-log", GameLogLine {
+            line: "Backing up worlds and settings...".to_string(),
+            is_error: false,
+            from_game: false,
+        });
+
+        let opts = launcher_opts.clone();
+        let dir = game_dir.to_path_buf();
+        let events_progress = events.clone();
+        let mut io = BackupIo::new(trigger)
+            .with_limit(max_io_mb_per_sec)
+            .on_progress(move |progress| events_progress.emit("backup-progress", progress.clone()));
+        let (result, skipped) = tokio::task::spawn_blocking(move || {
+            let result = backup_service::create_backup_of(&opts, &dir, trigger, &sources, &mut io);
+            (result, io.progress().skipped)
+        })
+        .await
+        .unwrap_or_else(|e| (Err(format!("Backup failed: {e}")), 0));
+
+        let (line, is_error) = match &result {
+            Ok(Some(name)) => {
+                backup_service::prune(launcher_opts);
+                match skipped {
+                    0 => (format!("Backup {} saved.", name), false),
+                    n => (format!("Backup {} saved without {} file(s) that kept changing.", name, n), true),
+                }
+            }
+            Ok(None) => ("Nothing to back up yet.".to_string(), false),
+            Err(e) => {
+                error!("{}", e);
+                (e.clone(), true)
+            }
+        };
+        events.emit("game-log", GameLogLine { line, is_error, from_game: false });
+
+        let (name, error) = match result {
+            Ok(name) => (name, None),
+            Err(e) => (None, Some(e)),
+        };
+        events.emit("backup-finished", BackupOutcome { trigger, name, skipped, error });
+    }
+
+    /// Backs up `periodic_backup.folders` every `interval_minutes` until the
+    /// process is no longer the running game.
+    fn spawn_backup_task(&self, pid: u32, launcher_opts: LauncherOptions, game_dir: PathBuf, events: EventSink) {
+        let running_arc = Arc::clone(&self.running);
+        let periodic = launcher_opts.periodic_backup.clone();
+        let period = std::time::Duration::from_secs(u64::from(periodic.interval_minutes.max(1)) * 60);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            // A slow backup pushes the next one back instead of starting it right away
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let still_running = running_arc.lock().unwrap().as_ref().map(|r| r.pid) == Some(pid);
+                if !still_running {
+                    break;
+                }
+                Self::take_backup(
+                    &launcher_opts,
+                    &game_dir,
+                    BackupTrigger::Periodic,
+                    periodic.folders.clone(),
+                    periodic.max_io_mb_per_sec,
+                    &events,
+                )
+                .await;
+            }
+            info!("Periodic backups stopped for PID {}", pid);
+        });
+    }
+
+    // -----------------------------------------------------------------------
+    // Metrics
+    // -----------------------------------------------------------------------
+
+    /// Samples the game process tree every `interval_secs` and emits `game-metrics`
+    /// until the process is no longer the running game.
+    fn spawn_metrics_task(&self, pid: u32, max_ram_mb: u32, interval_secs: u64, events: EventSink) {
+        let running_arc = Arc::clone(&self.running);
+        let metrics_arc = Arc::clone(&self.metrics);
+
+        tokio::spawn(async move {
+            let mut sampler = MetricsSampler::new(pid, max_ram_mb);
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            loop {
+                ticker.tick().await;
+                let still_running = running_arc.lock().unwrap().as_ref().map(|r| r.pid) == Some(pid);
+                if !still_running {
+                    break;
+                }
+                match sampler.sample() {
+                    Some(sample) => {
+                        *metrics_arc.lock().unwrap() = Some(sample.clone());
+                        events.emit("game-metrics", sample);
+                    }
+                    None => break,
+                }
+            }
+            info!("Metrics sampling stopped for PID {}", pid);
+        });
     }
 
     // -----------------------------------------------------------------------
     // Helpers
     // -----------------------------------------------------------------------
 
-    fn get_jar_path(launcher_opts: &LauncherOptions) -> PathBuf {
+    pub fn get_game_dir(launcher_opts: &LauncherOptions) -> PathBuf {
         launcher_opts
-            .launcher_dir
+            .game_dir
             .clone()
-            .unwrap_or_else(|| PathBuf::from(".permadeath-launcher"))
-            .join("tools")
+            .unwrap_or_else(|| PathBuf::from(".permadeath"))
+    }
+
+    fn get_mods_json_path(launcher_opts: &LauncherOptions) -> PathBuf {
+        Self::get_game_dir(launcher_opts).join("mods-list.json")
+    }
+
+    pub fn get_jar_path() -> PathBuf {
+        paths::tools_dir()
+            .unwrap_or_else(|| PathBuf::from(".permadeath-launcher").join("tools"))
             .join("minecraft-launcher-cli.jar")
     }
 
@@ -461,3 +1018,35 @@ impl GameManager {
         Ok(())
     }
 }
+
+/// Replaces the values of secret arguments (the Microsoft token) with a placeholder.
+fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = args.to_vec();
+    for i in 1..redacted.len() {
+        if args[i - 1] == "--microsoft" {
+            redacted[i] = REDACTED.to_string();
+        }
+    }
+    redacted
+}
+
+/// Full path of an executable found on `PATH`.
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let file = if cfg!(windows) { format!("{}.exe", name) } else { name.to_string() };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Quotes an argument for pasting into a terminal (POSIX shells, or cmd on Windows).
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+".contains(c) || (cfg!(windows) && c == '\\'));
+    if plain {
+        arg.to_string()
+    } else if cfg!(windows) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
@@ -0,0 +1,140 @@
+use std::fs::{File, OpenOptions, create_dir_all};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::Local;
+use log::{info, warn};
+
+use crate::models::options::LauncherOptions;
+
+/// Name of the captured output file inside each session folder.
+pub const SESSION_LOG_FILE: &str = "game.log";
+
+/// A recorded session as found on disk.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub id: String,
+    pub log_path: PathBuf,
+    pub log_size: u64,
+}
+
+/// A single run of the game, from launch to exit.
+///
+/// Each session gets its own folder under `launcher_dir/sessions` holding the
+/// captured game output and anything else produced while it ran (hook output,
+/// diagnostics).
+pub struct GameSession {
+    id: String,
+    username: String,
+    dir: PathBuf,
+    started: Instant,
+    log: Mutex<Option<File>>,
+}
+
+impl GameSession {
+    /// Creates the session folder and opens its log file.
+    /// A failure to create the log is non-fatal: the session simply isn't recorded.
+    pub fn start(launcher_opts: &LauncherOptions, username: &str) -> Self {
+        let id = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+        Self::open(launcher_opts, id, username, Instant::now())
+    }
+
+    /// Reopens the session of a game that was launched by an earlier launcher
+    /// process and has been running for `running_for`.
+    pub fn resume(launcher_opts: &LauncherOptions, id: &str, username: &str, running_for: Duration) -> Self {
+        let started = Instant::now().checked_sub(running_for).unwrap_or_else(Instant::now);
+        Self::open(launcher_opts, id.to_string(), username, started)
+    }
+
+    fn open(launcher_opts: &LauncherOptions, id: String, username: &str, started: Instant) -> Self {
+        let dir = Self::sessions_dir(launcher_opts).join(&id);
+
+        let log = create_dir_all(&dir)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(dir.join(SESSION_LOG_FILE)));
+
+        let log = match log {
+            Ok(f) => {
+                info!("Game session {} recorded at {:?}", id, dir);
+                Some(f)
+            }
+            Err(e) => {
+                warn!("Could not create session log in {:?}: {}", dir, e);
+                None
+            }
+        };
+
+        Self {
+            id,
+            username: username.to_string(),
+            dir,
+            started,
+            log: Mutex::new(log),
+        }
+    }
+
+    /// Folder holding one sub-folder per recorded session.
+    pub fn sessions_dir(launcher_opts: &LauncherOptions) -> PathBuf {
+        launcher_opts
+            .launcher_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(".permadeath-launcher"))
+            .join("sessions")
+    }
+
+    /// Recorded sessions, newest first.
+    pub fn list(launcher_opts: &LauncherOptions) -> Vec<SessionSummary> {
+        let mut sessions: Vec<SessionSummary> = std::fs::read_dir(Self::sessions_dir(launcher_opts))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| {
+                        let log_path = e.path().join(SESSION_LOG_FILE);
+                        let log_size = std::fs::metadata(&log_path).ok()?.len();
+                        Some(SessionSummary {
+                            id: e.file_name().to_string_lossy().into_owned(),
+                            log_path,
+                            log_size,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Ids are timestamps, so sorting them sorts by date
+        sessions.sort_by(|a, b| b.id.cmp(&a.id));
+        sessions
+    }
+
+    /// Looks up a recorded session by id.
+    pub fn find(launcher_opts: &LauncherOptions, id: &str) -> Option<SessionSummary> {
+        Self::list(launcher_opts).into_iter().find(|s| s.id == id)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        self.dir.join(SESSION_LOG_FILE)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Appends a line to the session log.
+    pub fn append(&self, line: &str) {
+        if let Some(file) = self.log.lock().unwrap().as_mut() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use log::{info, warn, error};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::models::options::HookCommand;
+use crate::services::game_session::GameSession;
+
+/// How long a hook's output is still read after it exited.
+const PIPE_DRAIN: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookKind {
+    PreLaunch,
+    PostExit,
+    OnCrash,
+}
+
+impl HookKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HookKind::PreLaunch => "pre-launch",
+            HookKind::PostExit => "post-exit",
+            HookKind::OnCrash => "on-crash",
+        }
+    }
+}
+
+/// Session details exposed to hooks through environment variables.
+pub struct HookContext<'a> {
+    pub game_dir: &'a Path,
+    /// `None` before the game has run.
+    pub exit_code: Option<i32>,
+    /// `None` before the game has run.
+    pub duration: Option<Duration>,
+}
+
+/// Runs a hook to completion, killing it once its timeout expires.
+///
+/// The hook inherits the launcher's environment plus:
+/// `PERMADEATH_HOOK`, `PERMADEATH_USERNAME`, `PERMADEATH_GAME_DIR`,
+/// `PERMADEATH_SESSION_ID`, `PERMADEATH_SESSION_DIR`, and after the game has
+/// run, `PERMADEATH_EXIT_CODE` and `PERMADEATH_SESSION_DURATION` (seconds).
+///
+/// Everything the hook prints is written to the session log.
+pub async fn run_hook(
+    kind: HookKind,
+    hook: &HookCommand,
+    ctx: &HookContext<'_>,
+    session: &GameSession,
+) -> Result<(), String> {
+    let label = kind.label();
+    info!("Running {} hook: {} {:?}", label, hook.command, hook.args);
+    session.append(&format!("[hook:{}] $ {} {}", label, hook.command, hook.args.join(" ")));
+
+    let mut cmd = tokio::process::Command::new(&hook.command);
+    cmd.args(&hook.args)
+        .current_dir(ctx.game_dir)
+        .env("PERMADEATH_HOOK", label)
+        .env("PERMADEATH_USERNAME", session.username())
+        .env("PERMADEATH_GAME_DIR", ctx.game_dir)
+        .env("PERMADEATH_SESSION_ID", session.id())
+        .env("PERMADEATH_SESSION_DIR", session.dir())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    if let Some(code) = ctx.exit_code {
+        cmd.env("PERMADEATH_EXIT_CODE", code.to_string());
+    }
+    if let Some(duration) = ctx.duration {
+        cmd.env("PERMADEATH_SESSION_DURATION", duration.as_secs().to_string());
+    }
+
+    let mut child = cmd.spawn().map_err(|e| {
+        let msg = format!("Failed to start {} hook '{}': {}", label, hook.command, e);
+        error!("{}", msg);
+        session.append(&format!("[hook:{}] {}", label, msg));
+        msg
+    })?;
+
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    // A hook that starts a program in the background (OBS, a server...) hands
+    // it its stdout and stderr, so the pipes may stay open long after the hook
+    // itself exits. Its exit is what ends the hook; the output left is read
+    // for a moment more.
+    let run = async {
+        let mut pipes = std::pin::pin!(async {
+            tokio::join!(
+                pipe_to_session(stdout, label, session),
+                pipe_to_session(stderr, label, session),
+            )
+        });
+        let (status, drained) = tokio::select! {
+            status = child.wait() => (status, false),
+            _ = &mut pipes => (child.wait().await, true),
+        };
+        if !drained {
+            let _ = tokio::time::timeout(PIPE_DRAIN, pipes).await;
+        }
+        status
+    };
+
+    let outcome = tokio::time::timeout(Duration::from_secs(hook.timeout_secs), run).await;
+    let result = match outcome {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("The {} hook exited with {}", label, status)),
+        Ok(Err(e)) => Err(format!("Error waiting for the {} hook: {}", label, e)),
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(format!("The {} hook timed out after {}s and was killed", label, hook.timeout_secs))
+        }
+    };
+
+    match &result {
+        Ok(_) => {
+            info!("{} hook finished", label);
+            session.append(&format!("[hook:{}] finished", label));
+        }
+        Err(msg) => {
+            warn!("{}", msg);
+            session.append(&format!("[hook:{}] {}", label, msg));
+        }
+    }
+
+    result
+}
+
+async fn pipe_to_session<R: AsyncRead + Unpin>(reader: R, label: &str, session: &GameSession) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        info!("[hook:{}] {}", label, line);
+        session.append(&format!("[hook:{}] {}", label, line));
+    }
+}
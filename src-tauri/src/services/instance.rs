@@ -0,0 +1,216 @@
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::{info, warn, error};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+use crate::models::options::LauncherOptions;
+
+/// Held with an exclusive lock for as long as the primary instance runs.
+const LOCK_FILE: &str = "instance.lock";
+/// Port and token of the primary instance's IPC listener. Kept apart from the
+/// lock file because Windows locks are mandatory and would block reading it.
+const ENDPOINT_FILE: &str = "instance.json";
+
+/// How long a second instance waits for the primary to publish its endpoint.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Forwarded launches download the JAR and mods before replying.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long the primary waits for a connected client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+struct Endpoint {
+    port: u16,
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ForwardRequest {
+    token: String,
+    args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ForwardReply {
+    ok: bool,
+    message: String,
+}
+
+/// Outcome of trying to become the only running launcher.
+pub enum Instance {
+    /// No other launcher runs; this process owns the game directory.
+    Primary(InstanceGuard),
+    /// Another launcher runs. The arguments were handed to it and this is its
+    /// answer; the caller should print it and exit.
+    Forwarded(Result<String, String>),
+}
+
+/// Proof of being the primary instance. Dropping it releases the lock.
+pub struct InstanceGuard {
+    _lock: File,
+    listener: TcpListener,
+    token: String,
+}
+
+/// Takes the instance lock in the launcher directory, or forwards the process
+/// arguments to the launcher holding it.
+///
+/// Fails only when the lock cannot be checked at all (no launcher directory,
+/// unwritable files); callers then run without single-instance protection.
+pub fn acquire(opts: &LauncherOptions) -> Result<Instance, String> {
+    let dir = opts
+        .launcher_dir
+        .as_ref()
+        .ok_or_else(|| "Launcher directory is not configured".to_string())?;
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))
+        .map_err(|e| format!("Could not open the instance lock: {e}"))?;
+
+    match lock.try_lock() {
+        Ok(()) => {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .map_err(|e| format!("Could not open the instance IPC listener: {e}"))?;
+            let endpoint = Endpoint {
+                port: listener.local_addr().map_err(|e| e.to_string())?.port(),
+                token: uuid::Uuid::new_v4().to_string(),
+            };
+            let json = serde_json::to_string(&endpoint).map_err(|e| e.to_string())?;
+            fs::write(dir.join(ENDPOINT_FILE), json)
+                .map_err(|e| format!("Could not publish the instance endpoint: {e}"))?;
+
+            info!("Running as the primary launcher instance (IPC port {})", endpoint.port);
+            Ok(Instance::Primary(InstanceGuard { _lock: lock, listener, token: endpoint.token }))
+        }
+        Err(fs::TryLockError::WouldBlock) => {
+            info!("Another launcher instance is running, forwarding arguments to it");
+            let args = std::env::args().skip(1).collect();
+            Ok(Instance::Forwarded(forward(&dir.join(ENDPOINT_FILE), args)))
+        }
+        Err(fs::TryLockError::Error(e)) => Err(format!("Could not lock the instance file: {e}")),
+    }
+}
+
+/// Sends `args` to the primary instance and waits for its reply.
+fn forward(endpoint_path: &Path, args: Vec<String>) -> Result<String, String> {
+    // The primary may hold the lock but not have written its endpoint yet
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    let (endpoint, stream) = loop {
+        let attempt = fs::read_to_string(endpoint_path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<Endpoint>(&data).ok())
+            .and_then(|ep| TcpStream::connect((Ipv4Addr::LOCALHOST, ep.port)).ok().map(|s| (ep, s)));
+        match attempt {
+            Some(connected) => break connected,
+            None if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(200)),
+            None => return Err("The running launcher did not answer.".to_string()),
+        }
+    };
+
+    let request = ForwardRequest { token: endpoint.token, args };
+    let mut line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+    line.push('\n');
+
+    let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+    (&stream)
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Could not reach the running launcher: {e}"))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("The running launcher did not answer: {e}"))?;
+    let reply: ForwardReply = serde_json::from_str(&reply)
+        .map_err(|_| "The running launcher sent an unexpected reply.".to_string())?;
+
+    if reply.ok { Ok(reply.message) } else { Err(reply.message) }
+}
+
+impl InstanceGuard {
+    /// Answers arguments forwarded by later launcher starts until the process
+    /// exits. `handler` receives the arguments (without the program name) and
+    /// returns the message shown by the forwarding process.
+    ///
+    /// Each connection is handled in its own task, so a client that never
+    /// sends anything, or a forwarded launch, does not hold up the others.
+    pub async fn serve<F, Fut>(self, handler: F)
+    where
+        F: Fn(Vec<String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let listener = match self
+            .listener
+            .set_nonblocking(true)
+            .and_then(|_| tokio::net::TcpListener::from_std(self.listener))
+        {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Could not listen for other launcher instances: {}", e);
+                return;
+            }
+        };
+        // Keeps the lock alive alongside the listener
+        let _lock = self._lock;
+        let token: Arc<str> = self.token.into();
+        let handler = Arc::new(handler);
+
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Instance IPC accept failed: {}", e);
+                    continue;
+                }
+            };
+            tokio::spawn(handle_connection(stream, Arc::clone(&token), Arc::clone(&handler)));
+        }
+    }
+}
+
+/// Reads one forwarded request, runs it and writes the reply.
+async fn handle_connection<F, Fut>(stream: tokio::net::TcpStream, token: Arc<str>, handler: Arc<F>)
+where
+    F: Fn(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = tokio::io::BufReader::new(read);
+    match tokio::time::timeout(REQUEST_TIMEOUT, reader.read_line(&mut line)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(_)) => return,
+        Err(_) => {
+            warn!("Dropped an instance IPC connection that sent no request");
+            return;
+        }
+    }
+
+    let reply = match serde_json::from_str::<ForwardRequest>(&line) {
+        Ok(req) if *req.token == *token => {
+            info!("Arguments forwarded by another launcher start: {:?}", req.args);
+            match handler(req.args).await {
+                Ok(message) => ForwardReply { ok: true, message },
+                Err(message) => ForwardReply { ok: false, message },
+            }
+        }
+        _ => {
+            warn!("Rejected an instance IPC request with a missing or wrong token");
+            return;
+        }
+    };
+
+    if let Ok(mut json) = serde_json::to_string(&reply) {
+        json.push('\n');
+        let _ = write.write_all(json.as_bytes()).await;
+    }
+}
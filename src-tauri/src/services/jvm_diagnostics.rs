@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::Local;
+use log::{info, warn, error};
+
+/// Diagnostics that can be captured from the running Minecraft JVM.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    ThreadDump,
+    HeapInfo,
+    ClassHistogram,
+}
+
+impl DiagnosticKind {
+    fn jcmd_command(&self) -> &'static str {
+        match self {
+            DiagnosticKind::ThreadDump => "Thread.print",
+            DiagnosticKind::HeapInfo => "GC.heap_info",
+            DiagnosticKind::ClassHistogram => "GC.class_histogram",
+        }
+    }
+
+    fn file_prefix(&self) -> &'static str {
+        match self {
+            DiagnosticKind::ThreadDump => "thread-dump",
+            DiagnosticKind::HeapInfo => "heap-info",
+            DiagnosticKind::ClassHistogram => "class-histogram",
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCapture {
+    pub kind: DiagnosticKind,
+    pub pid: u32,
+    /// Where the output was saved inside the session folder.
+    pub path: PathBuf,
+    pub output: String,
+}
+
+const JCMD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs a `jcmd` diagnostic against the Minecraft JVM started under `root_pid`
+/// and saves the output into `session_dir/diagnostics`.
+pub async fn capture(kind: DiagnosticKind, root_pid: u32, session_dir: &Path) -> Result<DiagnosticCapture, String> {
+    let pid = find_minecraft_jvm(root_pid);
+    let jcmd = find_jcmd(pid);
+    info!("Capturing {:?} from PID {} with {:?}", kind, pid, jcmd);
+
+    let output = tokio::time::timeout(
+        JCMD_TIMEOUT,
+        tokio::process::Command::new(&jcmd)
+            .arg(pid.to_string())
+            .arg(kind.jcmd_command())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| format!("jcmd did not answer within {}s.", JCMD_TIMEOUT.as_secs()))?
+    .map_err(|e| {
+        error!("Failed to run {:?}: {}", jcmd, e);
+        format!("Could not run jcmd ({}). Make sure a full JDK is installed.", e)
+    })?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        warn!("jcmd exited with {}: {}", output.status, stderr.trim());
+        return Err(format!("jcmd failed: {}", if stderr.trim().is_empty() { text.trim() } else { stderr.trim() }));
+    }
+    if !stderr.trim().is_empty() {
+        text.push_str(&stderr);
+    }
+
+    let dir = session_dir.join("diagnostics");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create diagnostics folder: {e}"))?;
+    let path = dir.join(format!(
+        "{}-{}.txt",
+        kind.file_prefix(),
+        Local::now().format("%H-%M-%S")
+    ));
+    std::fs::write(&path, &text)
+        .map_err(|e| format!("Failed to save diagnostic output: {e}"))?;
+
+    info!("{:?} saved to {:?}", kind, path);
+    Ok(DiagnosticCapture { kind, pid, path, output: text })
+}
+
+/// The game is started through the launcher CLI, which in turn starts the
+/// Minecraft JVM. Picks the descendant whose command line looks like Minecraft,
+/// falling back to the CLI process itself.
+#[cfg(target_os = "linux")]
+fn find_minecraft_jvm(root_pid: u32) -> u32 {
+    const MARKERS: [&str; 5] = [
+        "net.minecraft.client.main.Main",
+        "net.fabricmc.loader",
+        "cpw.mods.bootstraplauncher",
+        "--gameDir",
+        "--assetsDir",
+    ];
+
+    crate::services::process_metrics::process_tree(root_pid)
+        .into_iter()
+        .rev()
+        .find(|pid| {
+            std::fs::read(format!("/proc/{}/cmdline", pid))
+                .map(|raw| {
+                    let cmdline = String::from_utf8_lossy(&raw);
+                    MARKERS.iter().any(|m| cmdline.contains(m))
+                })
+                .unwrap_or(false)
+        })
+        .unwrap_or(root_pid)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn find_minecraft_jvm(root_pid: u32) -> u32 {
+    root_pid
+}
+
+/// Prefers the `jcmd` shipped with the runtime the game is running on, then
+/// `JAVA_HOME`, then whatever is on `PATH`.
+fn find_jcmd(pid: u32) -> PathBuf {
+    let exe_name = if cfg!(windows) { "jcmd.exe" } else { "jcmd" };
+
+    #[cfg(target_os = "linux")]
+    if let Ok(java) = std::fs::read_link(format!("/proc/{}/exe", pid)) {
+        if let Some(candidate) = java.parent().map(|bin| bin.join(exe_name)) {
+            if candidate.exists() {
+                return candidate;
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = pid;
+
+    if let Ok(home) = std::env::var("JAVA_HOME") {
+        let candidate = Path::new(&home).join("bin").join(exe_name);
+        if candidate.exists() {
+            return candidate;
+        }
+    }
+
+    PathBuf::from(exe_name)
+}
@@ -0,0 +1,211 @@
+use std::sync::OnceLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            "FATAL" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+}
+
+/// One entry of the game output.
+///
+/// Lines in the Minecraft/log4j layout (`[HH:MM:SS] [thread/LEVEL] [logger]: message`)
+/// are split into their parts. Anything else (game manager output, raw
+/// `System.out` prints) becomes a record with only a message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// `HH:MM:SS` as printed by the game.
+    pub time: Option<String>,
+    pub thread: Option<String>,
+    pub level: Option<LogLevel>,
+    pub logger: Option<String>,
+    /// The message, including folded continuation lines (stack traces).
+    pub message: String,
+    /// The record came from stderr.
+    pub is_error: bool,
+}
+
+fn header_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        // The logger is `[name]` in vanilla and `(name)` in Fabric's layout,
+        // and some layouts drop it altogether.
+        Regex::new(r"^\[(\d{2}:\d{2}:\d{2})(?:\.\d+)?\] \[([^\]]*)/([A-Z]+)\](?: [\[(]([^\])]+)[\])])?:? ?(.*)$")
+            .expect("log header pattern must be valid")
+    })
+}
+
+fn continuation_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        // Indented lines, stack trace chains and the exception line itself
+        Regex::new(r"^(?:\s|Caused by:|Suppressed:|\.\.\. \d+ more|[\w$]+(?:\.[\w$]+)+(?:Exception|Error|Throwable)\b)")
+            .expect("continuation pattern must be valid")
+    })
+}
+
+/// Parses a single line, if it starts a log4j record.
+pub fn parse_header(line: &str, is_error: bool) -> Option<LogRecord> {
+    let caps = header_pattern().captures(line)?;
+    let level = LogLevel::parse(&caps[3])?;
+    Some(LogRecord {
+        time: Some(caps[1].to_string()),
+        thread: Some(caps[2].to_string()),
+        level: Some(level),
+        logger: caps.get(4).map(|m| m.as_str().to_string()),
+        message: caps[5].to_string(),
+        is_error,
+    })
+}
+
+/// Whether a line that is not a record header belongs to the record before it.
+pub fn is_continuation(line: &str) -> bool {
+    continuation_pattern().is_match(line)
+}
+
+/// Groups the lines of one output stream into records.
+///
+/// A record is complete once the next one starts, so callers get it back from
+/// `push` one line late and should `flush` when the stream goes quiet.
+pub struct LogParser {
+    is_error: bool,
+    pending: Option<LogRecord>,
+}
+
+impl LogParser {
+    pub fn new(is_error: bool) -> Self {
+        Self { is_error, pending: None }
+    }
+
+    /// Feeds a line and returns the previous record if this line completes it.
+    pub fn push(&mut self, line: &str) -> Option<LogRecord> {
+        if let Some(record) = parse_header(line, self.is_error) {
+            return self.pending.replace(record);
+        }
+
+        if let Some(pending) = self.pending.as_mut() {
+            if is_continuation(line) {
+                pending.message.push('\n');
+                pending.message.push_str(line);
+                return None;
+            }
+        }
+
+        self.pending.replace(LogRecord {
+            time: None,
+            thread: None,
+            level: None,
+            logger: None,
+            message: line.to_string(),
+            is_error: self.is_error,
+        })
+    }
+
+    /// Returns the record being built, if any.
+    pub fn flush(&mut self) -> Option<LogRecord> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(lines: &[&str]) -> Vec<LogRecord> {
+        let mut parser = LogParser::new(false);
+        let mut records: Vec<LogRecord> = lines.iter().filter_map(|line| parser.push(line)).collect();
+        records.extend(parser.flush());
+        records
+    }
+
+    #[test]
+    fn parses_vanilla_header() {
+        let record = parse_header("[12:34:56] [Server thread/INFO] [minecraft/MinecraftServer]: Done (2.1s)!", false).unwrap();
+        assert_eq!(record.time.as_deref(), Some("12:34:56"));
+        assert_eq!(record.thread.as_deref(), Some("Server thread"));
+        assert_eq!(record.level, Some(LogLevel::Info));
+        assert_eq!(record.logger.as_deref(), Some("minecraft/MinecraftServer"));
+        assert_eq!(record.message, "Done (2.1s)!");
+    }
+
+    #[test]
+    fn parses_fabric_header_and_missing_logger() {
+        let fabric = parse_header("[12:34:56.789] [main/WARN] (FabricLoader): Mod is outdated", true).unwrap();
+        assert_eq!(fabric.level, Some(LogLevel::Warn));
+        assert_eq!(fabric.logger.as_deref(), Some("FabricLoader"));
+        assert_eq!(fabric.message, "Mod is outdated");
+        assert!(fabric.is_error);
+
+        let bare = parse_header("[12:34:56] [Render thread/ERROR]: Something broke", false).unwrap();
+        assert_eq!(bare.logger, None);
+        assert_eq!(bare.message, "Something broke");
+    }
+
+    #[test]
+    fn rejects_unknown_level() {
+        assert!(parse_header("[12:34:56] [main/VERBOSE]: hello", false).is_none());
+    }
+
+    #[test]
+    fn folds_stack_trace_into_record() {
+        let records = parse_all(&[
+            "[12:00:00] [main/ERROR] [minecraft/Main]: Crashed",
+            "java.lang.IllegalStateException: boom",
+            "\tat net.minecraft.Main.run(Main.java:10)",
+            "Caused by: java.io.IOException: disk full",
+            "\t... 3 more",
+            "[12:00:01] [main/INFO] [minecraft/Main]: Stopping",
+        ]);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].message,
+            "Crashed\njava.lang.IllegalStateException: boom\n\tat net.minecraft.Main.run(Main.java:10)\nCaused by: java.io.IOException: disk full\n\t... 3 more"
+        );
+        assert_eq!(records[1].message, "Stopping");
+    }
+
+    #[test]
+    fn unparseable_lines_become_plain_records() {
+        let records = parse_all(&[
+            "Downloading libraries...",
+            "[12:00:00] [main/INFO] [minecraft/Main]: Started",
+            "some raw System.out print",
+        ]);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].message, "Downloading libraries...");
+        assert_eq!(records[0].level, None);
+        assert_eq!(records[0].time, None);
+        assert_eq!(records[2].message, "some raw System.out print");
+        assert_eq!(records[2].level, None);
+    }
+
+    #[test]
+    fn records_are_returned_one_line_late() {
+        let mut parser = LogParser::new(false);
+        assert!(parser.push("[12:00:00] [main/INFO]: first").is_none());
+        assert_eq!(parser.push("[12:00:01] [main/INFO]: second").unwrap().message, "first");
+        assert_eq!(parser.flush().unwrap().message, "second");
+        assert!(parser.flush().is_none());
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::models::options::LauncherOptions;
+use crate::services::game_session::GameSession;
+use crate::services::log_parser::{self, LogLevel};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 500;
+const MAX_CONTEXT_LINES: usize = 20;
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// A timestamp this much earlier than the previous one means the session
+/// went past midnight; smaller steps back are out-of-order lines.
+const ROLLOVER_GAP_SECS: u64 = 12 * 60 * 60;
+
+/// A search over one session log.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogSearchQuery {
+    /// Session to search; the running or most recent session when `None`.
+    pub session_id: Option<String>,
+    /// Text to look for. Empty matches every line that passes the filters.
+    pub text: String,
+    /// Treat `text` as a regular expression.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Only lines of records with one of these levels.
+    pub levels: Option<Vec<LogLevel>>,
+    /// `HH:MM:SS` bounds (inclusive) on the game's timestamps. Each bound is
+    /// the first time of day it names after the session started, so a range
+    /// such as `23:30:00` to `00:30:00` spans midnight.
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Lines to include before and after each match.
+    pub context: usize,
+    /// Number of matches to skip, for pagination.
+    pub offset: usize,
+    /// Matches per page (100 by default).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSearchMatch {
+    /// 1-based line number in the session log.
+    pub line_number: usize,
+    pub line: String,
+    pub level: Option<LogLevel>,
+    pub time: Option<String>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSearchResult {
+    pub session_id: String,
+    /// Matches in the whole log, not just this page.
+    pub total_matches: usize,
+    pub offset: usize,
+    pub matches: Vec<LogSearchMatch>,
+}
+
+/// Searches a session log line by line, so only the requested page of
+/// matches is kept in memory. Continuation lines (stack traces) take the
+/// level and time of the record they belong to.
+pub fn search(launcher_opts: &LauncherOptions, query: &LogSearchQuery) -> Result<LogSearchResult, String> {
+    let session = match &query.session_id {
+        Some(id) => GameSession::find(launcher_opts, id)
+            .ok_or_else(|| format!("Session '{}' was not found.", id))?,
+        None => GameSession::list(launcher_opts)
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No game sessions recorded yet.".to_string())?,
+    };
+
+    let pattern = build_pattern(query)?;
+    let range = TimeRange::parse(query)?;
+    let file = File::open(&session.log_path)
+        .map_err(|e| format!("Could not open {}: {}", session.log_path.display(), e))?;
+    let (total, matches) = scan(BufReader::new(file), query, &pattern, &range)
+        .map_err(|e| format!("Could not read {}: {}", session.log_path.display(), e))?;
+
+    Ok(LogSearchResult {
+        session_id: session.id,
+        total_matches: total,
+        offset: query.offset,
+        matches,
+    })
+}
+
+/// Counts every matching line of `reader` and returns the page of matches
+/// selected by the query's offset and limit.
+fn scan(
+    reader: impl BufRead,
+    query: &LogSearchQuery,
+    pattern: &Regex,
+    range: &TimeRange,
+) -> io::Result<(usize, Vec<LogSearchMatch>)> {
+    let context = query.context.min(MAX_CONTEXT_LINES);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let mut matches: Vec<LogSearchMatch> = Vec::new();
+    let mut total = 0;
+    let mut before: VecDeque<String> = VecDeque::with_capacity(context + 1);
+    // Matches on this page that still collect lines after them
+    let mut open_from = 0;
+    let mut level: Option<LogLevel> = None;
+    let mut time: Option<String> = None;
+    // Seconds since midnight of the day the session started, counting the
+    // days the timestamps rolled over
+    let mut clock: Option<u64> = None;
+    let mut last_clock: Option<u64> = None;
+    let mut day = 0;
+    let mut bounds = None;
+
+    for (index, raw) in reader.split(b'\n').enumerate() {
+        let raw = raw?;
+        let line = String::from_utf8_lossy(&raw).trim_end_matches('\r').to_string();
+
+        for m in &mut matches[open_from..] {
+            m.after.push(line.clone());
+        }
+        while open_from < matches.len() && matches[open_from].after.len() >= context {
+            open_from += 1;
+        }
+
+        if let Some(record) = log_parser::parse_header(&line, false) {
+            level = record.level;
+            time = record.time;
+            clock = time.as_deref().and_then(parse_time).map(|secs| {
+                let mut at = day * DAY_SECS + secs;
+                if last_clock.is_some_and(|last| at + ROLLOVER_GAP_SECS < last) {
+                    day += 1;
+                    at += DAY_SECS;
+                }
+                last_clock = Some(at);
+                at
+            });
+            if bounds.is_none() {
+                bounds = clock.map(|start| range.anchor(start));
+            }
+        } else if !log_parser::is_continuation(&line) {
+            level = None;
+            time = None;
+            clock = None;
+        }
+
+        if passes_filters(query, level, clock, bounds) && pattern.is_match(&line) {
+            total += 1;
+            if total > query.offset && matches.len() < limit {
+                matches.push(LogSearchMatch {
+                    line_number: index + 1,
+                    line: line.clone(),
+                    level,
+                    time: time.clone(),
+                    before: before.iter().cloned().collect(),
+                    after: Vec::new(),
+                });
+                if context == 0 {
+                    open_from = matches.len();
+                }
+            }
+        }
+
+        if context > 0 {
+            if before.len() == context {
+                before.pop_front();
+            }
+            before.push_back(line);
+        }
+    }
+
+    Ok((total, matches))
+}
+
+fn build_pattern(query: &LogSearchQuery) -> Result<Regex, String> {
+    let source = if query.regex { query.text.clone() } else { regex::escape(&query.text) };
+    RegexBuilder::new(&source)
+        .case_insensitive(!query.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid regular expression: {}", e))
+}
+
+/// The query's `from` and `to`, in seconds since midnight.
+struct TimeRange {
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+impl TimeRange {
+    fn parse(query: &LogSearchQuery) -> Result<Self, String> {
+        let bound = |value: &Option<String>| match value.as_deref() {
+            None => Ok(None),
+            Some(text) => parse_time(text)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid time '{}', expected HH:MM:SS.", text)),
+        };
+        Ok(TimeRange { from: bound(&query.from)?, to: bound(&query.to)? })
+    }
+
+    /// Places the bounds on the session's clock, which starts at `start`:
+    /// each is the first such time at or after the start, and `to` comes
+    /// after `from`.
+    fn anchor(&self, start: u64) -> (Option<u64>, Option<u64>) {
+        let next = |secs: u64, after: u64| if secs < after { secs + DAY_SECS } else { secs };
+        let from = self.from.map(|secs| next(secs, start));
+        let to = self.to.map(|secs| next(secs, from.unwrap_or(start)));
+        (from, to)
+    }
+}
+
+/// Seconds since midnight of an `HH:MM:SS` time.
+fn parse_time(text: &str) -> Option<u64> {
+    let mut parts = text.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || h > 23 || m > 59 || s > 59 {
+        return None;
+    }
+    Some(h * 3600 + m * 60 + s)
+}
+
+/// `bounds` are the anchored time range, `None` until a timestamp was seen.
+fn passes_filters(
+    query: &LogSearchQuery,
+    level: Option<LogLevel>,
+    clock: Option<u64>,
+    bounds: Option<(Option<u64>, Option<u64>)>,
+) -> bool {
+    if let Some(levels) = &query.levels {
+        if !level.is_some_and(|l| levels.contains(&l)) {
+            return false;
+        }
+    }
+    if query.from.is_some() || query.to.is_some() {
+        let (Some(clock), Some((from, to))) = (clock, bounds) else { return false };
+        if from.is_some_and(|from| clock < from) || to.is_some_and(|to| clock > to) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ten records, `line 1` to `line 10`, alternating INFO and WARN.
+    fn sample_log() -> String {
+        (1..=10)
+            .map(|i| {
+                let level = if i % 2 == 0 { "WARN" } else { "INFO" };
+                format!("[12:00:{:02}] [main/{}] [test]: line {}\n", i, level, i)
+            })
+            .collect()
+    }
+
+    fn run(query: &LogSearchQuery) -> (usize, Vec<LogSearchMatch>) {
+        run_on(&sample_log(), query)
+    }
+
+    fn run_on(log: &str, query: &LogSearchQuery) -> (usize, Vec<LogSearchMatch>) {
+        let pattern = build_pattern(query).unwrap();
+        let range = TimeRange::parse(query).unwrap();
+        scan(log.as_bytes(), query, &pattern, &range).unwrap()
+    }
+
+    fn line_numbers(matches: &[LogSearchMatch]) -> Vec<usize> {
+        matches.iter().map(|m| m.line_number).collect()
+    }
+
+    #[test]
+    fn pages_through_matches() {
+        let query = LogSearchQuery { text: "line".into(), limit: Some(4), ..Default::default() };
+        let (total, first) = run(&query);
+        assert_eq!(total, 10);
+        assert_eq!(line_numbers(&first), vec![1, 2, 3, 4]);
+
+        let (total, second) = run(&LogSearchQuery { offset: 4, ..query.clone() });
+        assert_eq!(total, 10);
+        assert_eq!(line_numbers(&second), vec![5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn last_page_is_partial() {
+        let query = LogSearchQuery { text: "line".into(), limit: Some(4), offset: 8, ..Default::default() };
+        let (total, matches) = run(&query);
+        assert_eq!(total, 10);
+        assert_eq!(line_numbers(&matches), vec![9, 10]);
+    }
+
+    #[test]
+    fn offset_past_the_end_is_empty() {
+        let query = LogSearchQuery { text: "line".into(), offset: 50, ..Default::default() };
+        let (total, matches) = run(&query);
+        assert_eq!(total, 10);
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let query = LogSearchQuery { text: "line (".into(), regex: true, ..Default::default() };
+        let error = build_pattern(&query).unwrap_err();
+        assert!(error.starts_with("Invalid regular expression"), "{}", error);
+    }
+
+    #[test]
+    fn plain_text_is_not_a_regex() {
+        let query = LogSearchQuery { text: "line (".into(), ..Default::default() };
+        let (total, _) = run(&query);
+        assert_eq!(total, 0);
+    }
+
+    #[test]
+    fn case_sensitivity() {
+        let insensitive = LogSearchQuery { text: "LINE 1".into(), ..Default::default() };
+        assert_eq!(line_numbers(&run(&insensitive).1), vec![1, 10]);
+
+        let sensitive = LogSearchQuery { case_sensitive: true, ..insensitive };
+        assert_eq!(run(&sensitive).0, 0);
+    }
+
+    #[test]
+    fn filters_by_level_and_context() {
+        let query = LogSearchQuery {
+            text: "line".into(),
+            levels: Some(vec![LogLevel::Warn]),
+            context: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let (total, matches) = run(&query);
+        assert_eq!(total, 5);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].level, Some(LogLevel::Warn));
+        assert_eq!(matches[0].before, vec!["[12:00:01] [main/INFO] [test]: line 1"]);
+        assert_eq!(matches[0].after, vec!["[12:00:03] [main/INFO] [test]: line 3"]);
+    }
+
+    /// A session from 23:58 to 00:02 the next day, one record a minute.
+    fn midnight_log() -> String {
+        ["23:58:00", "23:59:00", "00:00:00", "00:01:00", "00:02:00"]
+            .iter()
+            .enumerate()
+            .map(|(i, time)| format!("[{}] [main/INFO] [test]: line {}\n", time, i + 1))
+            .collect()
+    }
+
+    #[test]
+    fn time_range_follows_the_session_past_midnight() {
+        let after = LogSearchQuery { from: Some("23:59:00".into()), ..Default::default() };
+        assert_eq!(line_numbers(&run_on(&midnight_log(), &after).1), vec![2, 3, 4, 5]);
+
+        let before = LogSearchQuery { to: Some("23:59:00".into()), ..Default::default() };
+        assert_eq!(line_numbers(&run_on(&midnight_log(), &before).1), vec![1, 2]);
+
+        let across = LogSearchQuery { from: Some("23:59:00".into()), to: Some("00:01:00".into()), ..Default::default() };
+        assert_eq!(line_numbers(&run_on(&midnight_log(), &across).1), vec![2, 3, 4]);
+
+        let next_day = LogSearchQuery { from: Some("00:01:00".into()), ..Default::default() };
+        assert_eq!(line_numbers(&run_on(&midnight_log(), &next_day).1), vec![4, 5]);
+    }
+
+    #[test]
+    fn invalid_time_is_an_error() {
+        let query = LogSearchQuery { from: Some("25:00".into()), ..Default::default() };
+        assert!(TimeRange::parse(&query).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::Path;
+use log::{info, error};
+
+use crate::services::redaction;
+
+/// mclo.gs API endpoint used when `LOG_SHARE_URL` is not set.
+const DEFAULT_SHARE_URL: &str = "https://api.mclo.gs/1/log";
+
+/// Paste services reject very long logs; the end of the log is the useful part.
+const MAX_SHARED_LINES: usize = 25_000;
+
+#[derive(serde::Deserialize)]
+struct PasteResponse {
+    success: bool,
+    url: Option<String>,
+    error: Option<String>,
+}
+
+/// Redacts a log file and uploads it to the paste service.
+///
+/// The endpoint is read from `LOG_SHARE_URL` and must implement the mclo.gs
+/// API: a form POST with a `content` field answered by
+/// `{"success": true, "url": "..."}` or `{"success": false, "error": "..."}`.
+/// Returns the URL of the uploaded log.
+pub async fn share_log(log_path: &Path) -> Result<String, String> {
+    let raw = tokio::fs::read(log_path)
+        .await
+        .map_err(|e| format!("Could not read the log: {e}"))?;
+    let text = String::from_utf8_lossy(&raw);
+
+    let lines: Vec<&str> = text.lines().collect();
+    let skipped = lines.len().saturating_sub(MAX_SHARED_LINES);
+    let mut content = String::new();
+    if skipped > 0 {
+        content.push_str(&format!("[{} earlier lines omitted]\n", skipped));
+    }
+    content.push_str(&lines[skipped..].join("\n"));
+
+    let content = redaction::redact(&content);
+
+    let endpoint = std::env::var("LOG_SHARE_URL")
+        .ok()
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| DEFAULT_SHARE_URL.to_string());
+
+    info!("Uploading {:?} ({} bytes) to {}", log_path, content.len(), endpoint);
+
+    let body = reqwest::Client::new()
+        .post(&endpoint)
+        .form(&[("content", content.as_str())])
+        .send()
+        .await
+        .map_err(|e| {
+            error!("Log upload failed: {}", e);
+            format!("Could not reach the paste service: {e}")
+        })?
+        .text()
+        .await
+        .map_err(|e| format!("Could not read the paste service response: {e}"))?;
+
+    let response: PasteResponse = serde_json::from_str(&body).map_err(|e| {
+        error!("Unexpected paste service response: {} ({})", body, e);
+        "The paste service returned an unexpected response.".to_string()
+    })?;
+
+    match (response.success, response.url) {
+        (true, Some(url)) => {
+            info!("Log shared at {}", url);
+            Ok(url)
+        }
+        _ => Err(format!(
+            "The paste service rejected the log: {}",
+            response.error.unwrap_or_else(|| "unknown error".to_string())
+        )),
+    }
+}
@@ -4,3 +4,23 @@ pub mod java_installer;
 pub mod options_repository;
 pub mod game_service;
 pub mod server_status_service;
+pub mod game_session;
+pub mod hook_service;
+pub mod process_priority;
+pub mod process_metrics;
+pub mod jvm_diagnostics;
+pub mod diagnosis_service;
+pub mod redaction;
+pub mod log_share_service;
+pub mod event_sink;
+pub mod deep_link;
+pub mod instance;
+pub mod game_lock;
+pub mod log_parser;
+pub mod log_search;
+pub mod backup_service;
+pub mod backup_store;
+pub mod settings_bundle;
+pub mod options_migration;
+pub mod options_watcher;
+pub mod game_dir_mover;
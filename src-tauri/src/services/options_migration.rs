@@ -0,0 +1,518 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Local};
+use log::{error, info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::paths;
+use crate::models::options::{GAME_SCHEMA_VERSION, LAUNCHER_SCHEMA_VERSION, MAX_RAM_MB, MIN_RAM_MB};
+use crate::services::options_repository::{last_good_path, write_atomic};
+
+/// Upgrades a file's JSON from one schema version to the next.
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Step `i` migrates `options.json` from version `i` to `i + 1`.
+const LAUNCHER_MIGRATIONS: [Migration; LAUNCHER_SCHEMA_VERSION as usize] = [launcher_v0_to_v1];
+/// Step `i` migrates `game_options.json` from version `i` to `i + 1`.
+const GAME_MIGRATIONS: [Migration; GAME_SCHEMA_VERSION as usize] = [game_v0_to_v1, game_v1_to_v2];
+
+/// Something about the options files the user should know, shown by the UI
+/// at startup.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsNotice {
+    pub file: String,
+    pub message: String,
+    /// Copy of the file as it was before the launcher touched it.
+    pub backup: Option<PathBuf>,
+}
+
+static NOTICES: Mutex<Vec<OptionsNotice>> = Mutex::new(Vec::new());
+
+/// Notices collected since the last call.
+pub fn take_notices() -> Vec<OptionsNotice> {
+    std::mem::take(&mut *NOTICES.lock().unwrap())
+}
+
+/// Options are loaded again by most commands, so the same problem is only reported once.
+fn notify(notice: OptionsNotice) {
+    let mut notices = NOTICES.lock().unwrap();
+    if !notices.contains(&notice) {
+        notices.push(notice);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum OptionsFile {
+    Launcher,
+    Game,
+}
+
+impl OptionsFile {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            OptionsFile::Launcher => "options.json",
+            OptionsFile::Game => "game_options.json",
+        }
+    }
+
+    pub fn current_version(&self) -> u32 {
+        match self {
+            OptionsFile::Launcher => LAUNCHER_SCHEMA_VERSION,
+            OptionsFile::Game => GAME_SCHEMA_VERSION,
+        }
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        match self {
+            OptionsFile::Launcher => &LAUNCHER_MIGRATIONS,
+            OptionsFile::Game => &GAME_MIGRATIONS,
+        }
+    }
+}
+
+/// Parses an options file, migrating it to the current schema first.
+///
+/// Before a migration the original file is copied to `<name>.v<version>.bak`
+/// and the migrated file is written back. A damaged file is copied aside and
+/// replaced with its last-known-good copy. Files from a newer launcher and
+/// files that cannot be recovered are reported through `take_notices` instead
+/// of being silently replaced by defaults.
+///
+/// Returns `None` when the caller should fall back to defaults.
+pub fn load<T: DeserializeOwned>(file: OptionsFile, path: &Path, data: &str) -> Option<T> {
+    let name = file.file_name();
+    let problem = match parse::<T>(file, path, data) {
+        Ok(options) => {
+            let good = last_good_path(path);
+            if options.is_some() && !good.exists() {
+                if let Err(e) = std::fs::copy(path, &good) {
+                    warn!("Could not keep a last-known-good copy of {}: {}", name, e);
+                }
+            }
+            return options;
+        }
+        Err(problem) => problem,
+    };
+
+    error!("{} is damaged: {}", name, problem);
+    let backup = keep_copy(path, "invalid", true);
+    let (options, message) = match restore_last_good::<T>(file, path) {
+        Some((options, saved_at)) => (
+            Some(options),
+            format!(
+                "{} was damaged ({}) and was restored from the copy saved on {}.",
+                name,
+                problem,
+                saved_at.format("%Y-%m-%d %H:%M")
+            ),
+        ),
+        None => (None, format!("{} was damaged ({}), so defaults are used.", name, problem)),
+    };
+    notify(OptionsNotice {
+        file: name.to_string(),
+        message,
+        backup,
+    });
+    options
+}
+
+/// Replaces a damaged options file with its last-known-good copy, if that one is usable.
+fn restore_last_good<T: DeserializeOwned>(file: OptionsFile, path: &Path) -> Option<(T, DateTime<Local>)> {
+    let good = last_good_path(path);
+    let data = std::fs::read_to_string(&good).ok()?;
+    let saved_at = std::fs::metadata(&good)
+        .and_then(|m| m.modified())
+        .map(DateTime::<Local>::from)
+        .unwrap_or_else(|_| Local::now());
+
+    let options = match parse::<T>(file, &good, &data) {
+        Ok(Some(options)) => options,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("The last-known-good copy of {} is unusable too: {}", file.file_name(), e);
+            return None;
+        }
+    };
+
+    // Re-read in case parsing migrated the copy
+    let restored = std::fs::read(&good).and_then(|data| write_atomic(path, &data));
+    match restored {
+        Ok(_) => info!("Restored {:?} from {:?}", path, good),
+        Err(e) => warn!("Could not restore {:?} from {:?}: {}", path, good, e),
+    }
+    Some((options, saved_at))
+}
+
+/// Parses and migrates one file. `Ok(None)` is a file from a newer launcher
+/// that could not be used (already reported); `Err` means the file is damaged.
+pub(crate) fn parse<T: DeserializeOwned>(file: OptionsFile, path: &Path, data: &str) -> Result<Option<T>, String> {
+    let name = file.file_name();
+    let mut value: Value = serde_json::from_str(data).map_err(|e| format!("invalid JSON: {}", e))?;
+
+    let version = schema_version(&value);
+    let current = file.current_version();
+
+    if version > current {
+        // Unknown fields are ignored, so a newer file often still loads. What
+        // is kept is in the current layout, so it is saved as such
+        let backup = keep_copy(path, &format!("v{}", version), false);
+        value["schema_version"] = Value::from(current);
+        let parsed = serde_json::from_value::<T>(value).ok();
+        let outcome = if parsed.is_some() {
+            "settings this launcher knows were loaded, and saving will drop the others"
+        } else {
+            "defaults are used"
+        };
+        warn!("{} has schema version {}, newer than {}", name, version, current);
+        notify(OptionsNotice {
+            file: name.to_string(),
+            message: format!(
+                "{} was written by a newer launcher (schema {}, this one supports {}); {}.",
+                name, version, current, outcome
+            ),
+            backup,
+        });
+        return Ok(parsed);
+    }
+
+    if version < current {
+        if keep_copy(path, &format!("v{}", version), false).is_none() {
+            // Without a copy of the original, leave the file alone
+            notify(OptionsNotice {
+                file: name.to_string(),
+                message: format!("{} could not be backed up, so it was not upgraded.", name),
+                backup: None,
+            });
+            return Ok(None);
+        }
+
+        upgrade(file, &mut value, version)?;
+
+        match serde_json::to_string_pretty(&value) {
+            Ok(json) => match write_atomic(path, json.as_bytes()) {
+                Ok(_) => info!("Migrated {:?} from schema version {} to {}", path, version, current),
+                Err(e) => warn!("Could not write the migrated {}: {}", name, e),
+            },
+            Err(e) => warn!("Could not serialize the migrated {}: {}", name, e),
+        }
+    }
+
+    serde_json::from_value::<T>(value)
+        .map(Some)
+        .map_err(|e| format!("invalid settings: {}", e))
+}
+
+/// Brings options JSON that is not a file in the launcher directory (such as
+/// an entry of a settings bundle) to the current schema, with no backups or
+/// notices. A newer file is relabelled as current, like `parse` does; its
+/// original schema version is returned so the caller can mention it.
+pub(crate) fn migrate(file: OptionsFile, value: &mut Value) -> Result<u32, String> {
+    let version = schema_version(value);
+    let current = file.current_version();
+    if version > current {
+        value["schema_version"] = Value::from(current);
+    } else if version < current {
+        upgrade(file, value, version)?;
+    }
+    Ok(version)
+}
+
+fn schema_version(value: &Value) -> u32 {
+    value.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Runs the migration steps from `version` up to the current schema.
+fn upgrade(file: OptionsFile, value: &mut Value, version: u32) -> Result<(), String> {
+    for (step, migration) in file.migrations().iter().enumerate().skip(version as usize) {
+        migration(value).map_err(|e| format!("upgrade from schema {} failed: {}", step, e))?;
+        value["schema_version"] = Value::from(step as u32 + 1);
+    }
+    Ok(())
+}
+
+/// Copies `path` to `<path>.<tag>.bak`. Unless `replace` is set, a copy left
+/// by an earlier run is kept, so it still holds the file as it first was.
+fn keep_copy(path: &Path, tag: &str, replace: bool) -> Option<PathBuf> {
+    let mut name = path.file_name()?.to_os_string();
+    name.push(format!(".{}.bak", tag));
+    let backup = path.with_file_name(name);
+    if backup.exists() && !replace {
+        return Some(backup);
+    }
+    match std::fs::copy(path, &backup) {
+        Ok(_) => {
+            info!("Copied {:?} to {:?}", path, backup);
+            Some(backup)
+        }
+        Err(e) => {
+            warn!("Could not copy {:?} to {:?}: {}", path, backup, e);
+            None
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+/// Adds the fields introduced without `#[serde(default)]` before the schema
+/// was versioned (`automatic_backup`, `microsoft_token`, ...).
+///
+/// The defaults are written out as they were at schema 1 rather than taken
+/// from `LauncherOptions::new()`, so fields added by later schemas are left
+/// to the steps that introduce them.
+fn launcher_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let defaults = serde_json::json!({
+        // Where this computer keeps the files, not a schema default
+        "launcher_dir": paths::launcher_dir(),
+        "game_dir": paths::default_game_dir(),
+        "init_on_start": false,
+        "auto_update": true,
+        "notification_enabled": false,
+        "debug_console": false,
+        "automatic_backup": true,
+        "microsoft_token": null,
+    });
+    fill_missing(value, &defaults)
+}
+
+/// Schema 1 defaults of `game_options.json`; see `launcher_v0_to_v1`.
+fn game_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let defaults = serde_json::json!({
+        "max_ram": 4096,
+        "vm_flags": [
+            "-XX:+UnlockExperimentalVMOptions",
+            "-XX:MaxGCPauseMillis=100",
+            "-XX:G1NewSizePercent=30",
+            "-XX:G1ReservePercent=20",
+            "-XX:+UseStringDeduplication",
+            "-XX:G1HeapRegionSize=32M",
+            "-XX:+TieredCompilation",
+            "-XX:+AlwaysPreTouch",
+            "-Dsun.java2d.opengl=true",
+            "-Xverify:none",
+        ],
+        "garbage_collector": "G1GC",
+    });
+    fill_missing(value, &defaults)
+}
+
+/// Collectors selected with `-XX:+Use...GC` and their `garbage_collector` value.
+const GC_SELECTORS: [(&str, &str); 5] = [
+    ("UseSerialGC", "Serial"),
+    ("UseParallelGC", "Parallel"),
+    ("UseG1GC", "G1GC"),
+    ("UseZGC", "ZGC"),
+    ("UseShenandoahGC", "Shenandoah"),
+];
+
+/// Takes the collector and heap size out of `vm_flags`, which now reject them.
+/// An enabled collector flag becomes `garbage_collector` and `-Xmx` becomes
+/// `max_ram`; other selectors and `-Xms` are dropped.
+fn game_v1_to_v2(value: &mut Value) -> Result<(), String> {
+    let Some(fields) = value.as_object_mut() else {
+        return Err("expected a JSON object".to_string());
+    };
+    let Some(Value::Array(flags)) = fields.get("vm_flags") else {
+        return Ok(());
+    };
+
+    let mut collector = None;
+    let mut max_ram = None;
+    let mut kept = Vec::new();
+    for flag in flags {
+        let Some(text) = flag.as_str() else {
+            kept.push(flag.clone());
+            continue;
+        };
+        if let Some(option) = text.strip_prefix("-XX:+").or_else(|| text.strip_prefix("-XX:-")) {
+            if option.starts_with("Use") && option.ends_with("GC") {
+                if text.starts_with("-XX:+") {
+                    if let Some((_, gc)) = GC_SELECTORS.iter().find(|(name, _)| *name == option) {
+                        collector = Some(*gc);
+                    }
+                }
+                continue;
+            }
+        }
+        if let Some(size) = text.strip_prefix("-Xmx") {
+            max_ram = heap_size_mb(size).or(max_ram);
+            continue;
+        }
+        if text.starts_with("-Xms") {
+            continue;
+        }
+        kept.push(flag.clone());
+    }
+
+    fields.insert("vm_flags".to_string(), Value::Array(kept));
+    if let Some(gc) = collector {
+        fields.insert("garbage_collector".to_string(), Value::from(gc));
+    }
+    if let Some(mb) = max_ram {
+        fields.insert("max_ram".to_string(), Value::from(mb.clamp(MIN_RAM_MB, MAX_RAM_MB)));
+    }
+    Ok(())
+}
+
+/// A JVM memory size (`4G`, `4096m`, `4194304k`, bytes) in MB.
+fn heap_size_mb(size: &str) -> Option<u32> {
+    let (number, unit) = match size.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&size[..i], c.to_ascii_lowercase()),
+        _ => (size, 'b'),
+    };
+    let number: u64 = number.parse().ok()?;
+    let mb = match unit {
+        'g' => number.checked_mul(1024)?,
+        'm' => number,
+        'k' => number / 1024,
+        'b' => number / (1024 * 1024),
+        _ => return None,
+    };
+    u32::try_from(mb).ok()
+}
+
+/// Copies into `value` the fields of `defaults` it lacks, recursing into objects.
+fn fill_missing(value: &mut Value, defaults: &Value) -> Result<(), String> {
+    let (Value::Object(target), Value::Object(defaults)) = (value, defaults) else {
+        return Err("expected a JSON object".to_string());
+    };
+    for (key, default) in defaults {
+        match target.get_mut(key) {
+            Some(existing) if existing.is_object() && default.is_object() => fill_missing(existing, default)?,
+            Some(_) => {}
+            None => {
+                target.insert(key.clone(), default.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::options::GameOptions;
+
+    /// A fresh folder holding `game_options.json` with `data`.
+    fn write_game_options(data: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("options-migration-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(OptionsFile::Game.file_name());
+        std::fs::write(&path, data).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn v0_file_keeps_its_values() {
+        let mut v0 = serde_json::to_value(GameOptions::new()).unwrap();
+        let fields = v0.as_object_mut().unwrap();
+        fields.remove("schema_version");
+        fields.remove("garbage_collector");
+        fields.insert("max_ram".to_string(), Value::from(8192));
+        let (dir, path) = write_game_options(&v0.to_string());
+
+        let options = parse::<GameOptions>(OptionsFile::Game, &path, &v0.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.get_max_ram(), 8192);
+        assert_eq!(options.schema_version, GAME_SCHEMA_VERSION);
+
+        // The upgraded file is written back and the original kept aside
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], GAME_SCHEMA_VERSION);
+        assert_eq!(saved["max_ram"], 8192);
+        assert!(saved.get("garbage_collector").is_some());
+        assert!(dir.join("game_options.json.v0.bak").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn newer_file_loads_with_a_notice() {
+        let mut newer = serde_json::to_value(GameOptions::new()).unwrap();
+        newer["schema_version"] = Value::from(GAME_SCHEMA_VERSION + 1);
+        newer["max_ram"] = Value::from(6144);
+        newer["field_from_the_future"] = Value::from(true);
+        let (dir, path) = write_game_options(&newer.to_string());
+
+        let options = parse::<GameOptions>(OptionsFile::Game, &path, &newer.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.get_max_ram(), 6144);
+
+        let notices = take_notices();
+        let notice = notices
+            .iter()
+            .find(|n| n.message.contains("newer launcher"))
+            .expect("a notice about the newer file");
+        assert_eq!(notice.file, "game_options.json");
+        let backup = dir.join(format!("game_options.json.v{}.bak", GAME_SCHEMA_VERSION + 1));
+        assert_eq!(notice.backup.as_deref(), Some(backup.as_path()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        let (dir, path) = write_game_options("{ \"max_ram\": ");
+        let result = parse::<GameOptions>(OptionsFile::Game, &path, "{ \"max_ram\": ");
+        assert!(result.unwrap_err().starts_with("invalid JSON"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrate_upgrades_without_touching_files() {
+        let mut v0 = serde_json::json!({ "max_ram": 8192 });
+        assert_eq!(migrate(OptionsFile::Game, &mut v0).unwrap(), 0);
+        assert_eq!(v0["schema_version"], GAME_SCHEMA_VERSION);
+        assert_eq!(v0["max_ram"], 8192);
+
+        let mut newer = serde_json::json!({ "schema_version": GAME_SCHEMA_VERSION + 1 });
+        assert_eq!(migrate(OptionsFile::Game, &mut newer).unwrap(), GAME_SCHEMA_VERSION + 1);
+        assert_eq!(newer["schema_version"], GAME_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn v1_flags_move_to_their_own_settings() {
+        let mut v1 = serde_json::json!({
+            "schema_version": 1,
+            "max_ram": 4096,
+            "garbage_collector": "G1GC",
+            "vm_flags": ["-XX:+AlwaysPreTouch", "-XX:+UseZGC", "-Xmx6G", "-Xms2G", "-XX:-UseSerialGC", "-Xss4M"],
+        });
+        game_v1_to_v2(&mut v1).unwrap();
+        assert_eq!(v1["vm_flags"], serde_json::json!(["-XX:+AlwaysPreTouch", "-Xss4M"]));
+        assert_eq!(v1["garbage_collector"], "ZGC");
+        assert_eq!(v1["max_ram"], 6144);
+
+        // Nothing to move: the file is left as it was
+        let mut clean = serde_json::json!({ "max_ram": 8192, "garbage_collector": "G1GC", "vm_flags": ["-XX:+AlwaysPreTouch"] });
+        let before = clean.clone();
+        game_v1_to_v2(&mut clean).unwrap();
+        assert_eq!(clean, before);
+    }
+
+    #[test]
+    fn heap_sizes_in_mb() {
+        assert_eq!(heap_size_mb("4G"), Some(4096));
+        assert_eq!(heap_size_mb("3072m"), Some(3072));
+        assert_eq!(heap_size_mb("2097152k"), Some(2048));
+        assert_eq!(heap_size_mb("1073741824"), Some(1024));
+        assert_eq!(heap_size_mb("lots"), None);
+        assert_eq!(heap_size_mb(""), None);
+    }
+
+    #[test]
+    fn fill_missing_only_adds_fields() {
+        let mut value = serde_json::json!({ "a": 1, "nested": { "x": "kept" } });
+        let defaults = serde_json::json!({ "a": 2, "b": 3, "nested": { "x": "default", "y": 4 } });
+        fill_missing(&mut value, &defaults).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": 1, "b": 3, "nested": { "x": "kept", "y": 4 } }));
+
+        assert!(fill_missing(&mut Value::from(1), &defaults).is_err());
+    }
+}
@@ -1,38 +1,76 @@
-use std::fs::{write, create_dir_all};
-use log::{info, error};
+use std::fs::{create_dir_all, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use log::{info, error, warn};
 
+use crate::core::paths;
 use crate::models::options::{LauncherOptions, GameOptions};
+use crate::services::options_migration::{self, OptionsFile};
+use crate::services::options_watcher;
 
 /// Handles all file I/O operations for options (Repository Pattern)
 pub struct OptionsRepository;
 
 impl OptionsRepository {
-    pub fn save_launcher_options(options: &LauncherOptions) {
-        if let Some(dir) = &options.launcher_dir {
+    /// Writes `options.json`. Errors are logged and returned for the UI.
+    ///
+    /// A `launcher_dir` other than the current one moves the launcher there
+    /// from the next start: the options are written to both folders and the
+    /// location pointer is updated.
+    pub fn save_launcher_options(options: &LauncherOptions) -> Result<(), String> {
+        let Some(dir) = &options.launcher_dir else {
+            info!("Launcher directory is not configured.");
+            return Err("The launcher directory is not configured.".to_string());
+        };
+        let mut stored = options.clone();
+        stored.game_dir = options.game_dir.as_deref().map(paths::to_stored);
+        let json = serde_json::to_string_pretty(&stored).map_err(|e| {
+            error!("Could not serialize options to JSON: {}", e);
+            format!("Could not serialize the options: {}", e)
+        })?;
+
+        let current = paths::launcher_dir();
+        if current.as_ref() != Some(dir) {
+            Self::relocate(current.as_deref(), dir)?;
+        }
+
+        let mut targets = vec![dir.clone()];
+        targets.extend(current.filter(|c| c != dir));
+        for dir in targets {
             let options_path = dir.join("options.json");
             info!("Trying to save options at: {:?}", options_path);
-            let json = match serde_json::to_string_pretty(options) {
-                Ok(j) => j,
-                Err(_) => {
-                    error!("Could not serialize options to JSON.");
-                    return;
-                }
-            };
-            if let Err(e) = create_dir_all(dir) {
+            create_dir_all(&dir).map_err(|e| {
                 error!("Failed to create config directory: {}", e);
-                return;
-            }
+                format!("Could not create {}: {}", dir.display(), e)
+            })?;
             info!("Config directory created or already exists: {:?}", dir);
-            if let Err(e) = write(&options_path, json) {
+            write_options_file(&options_path, &json).map_err(|e| {
                 error!("Failed to write options file: {}", e);
-                return;
-            }
+                format!("Could not write {}: {}", options_path.display(), e)
+            })?;
             info!("Options saved successfully at: {:?}", options_path);
-            return;
         }
-        info!("Launcher directory is not configured.");
+        Ok(())
     }
 
+    /// Points the launcher at `target` and brings the game options along, so
+    /// the next start finds them there.
+    fn relocate(current: Option<&Path>, target: &Path) -> Result<(), String> {
+        paths::relocate(target)?;
+        if let Some(current) = current {
+            let from = current.join("game_options.json");
+            let to = target.join("game_options.json");
+            if from.exists() && !to.exists() {
+                if let Err(e) = std::fs::copy(&from, &to) {
+                    warn!("Could not copy {:?} to {:?}: {}", from, to, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `options.json` from the launcher directory resolved by
+    /// `core::paths`, which also decides `launcher_dir` whatever the file says.
     pub fn load_launcher_options() -> LauncherOptions {
         let default_options = LauncherOptions::new();
         if let Some(dir) = &default_options.launcher_dir {
@@ -46,13 +84,15 @@ impl OptionsRepository {
                 }
             };
             info!("Options file found, trying to deserialize...");
-            let options = match serde_json::from_str::<LauncherOptions>(&data) {
-                Ok(o) => o,
-                Err(_) => {
+            let mut options = match options_migration::load::<LauncherOptions>(OptionsFile::Launcher, &options_path, &data) {
+                Some(o) => o,
+                None => {
                     error!("Could not deserialize options file.");
                     return default_options;
                 }
             };
+            options.launcher_dir = default_options.launcher_dir.clone();
+            options.game_dir = options.game_dir.as_deref().map(paths::from_stored);
             info!("Options loaded successfully.");
             return options;
         }
@@ -71,30 +111,29 @@ impl OptionsRepository {
         false
     }
 
-    pub fn save_game_options(game_options: &GameOptions, launcher_options: &LauncherOptions) {
+    /// Writes `game_options.json`. Errors are logged and returned for the UI.
+    pub fn save_game_options(game_options: &GameOptions, launcher_options: &LauncherOptions) -> Result<(), String> {
         info!("Saving game options: {:?}", game_options);
-        if let Some(dir) = &launcher_options.launcher_dir {
-            let options_path = dir.join("game_options.json");
-            let json = match serde_json::to_string_pretty(game_options) {
-                Ok(j) => j,
-                Err(e) => {
-                    error!("Failed to serialize game options: {}", e);
-                    return;
-                }
-            };
-            if let Err(e) = create_dir_all(dir) {
-                error!("Failed to create config directory: {}", e);
-                return;
-            }
-            info!("Config directory created or already exists.");
-            if let Err(e) = write(&options_path, json) {
-                error!("Failed to write game options file: {}", e);
-                return;
-            }
-            info!("Game options saved successfully at: {:?}", options_path);
-            return;
-        }
-        info!("Launcher directory is not configured.");
+        let Some(dir) = &launcher_options.launcher_dir else {
+            info!("Launcher directory is not configured.");
+            return Err("The launcher directory is not configured.".to_string());
+        };
+        let options_path = dir.join("game_options.json");
+        let json = serde_json::to_string_pretty(game_options).map_err(|e| {
+            error!("Failed to serialize game options: {}", e);
+            format!("Could not serialize the game options: {}", e)
+        })?;
+        create_dir_all(dir).map_err(|e| {
+            error!("Failed to create config directory: {}", e);
+            format!("Could not create {}: {}", dir.display(), e)
+        })?;
+        info!("Config directory created or already exists.");
+        write_options_file(&options_path, &json).map_err(|e| {
+            error!("Failed to write game options file: {}", e);
+            format!("Could not write {}: {}", options_path.display(), e)
+        })?;
+        info!("Game options saved successfully at: {:?}", options_path);
+        Ok(())
     }
 
     pub fn load_game_options(launcher_options: &LauncherOptions) -> GameOptions {
@@ -102,13 +141,13 @@ impl OptionsRepository {
             let options_path = dir.join("game_options.json");
             info!("Trying to load game options from: {:?}", options_path);
             if let Ok(data) = std::fs::read_to_string(&options_path) {
-                match serde_json::from_str::<GameOptions>(&data) {
-                    Ok(options) => {
+                match options_migration::load::<GameOptions>(OptionsFile::Game, &options_path, &data) {
+                    Some(options) => {
                         info!("Game options loaded successfully: {:?}", options);
                         return options;
                     }
-                    Err(e) => {
-                        error!("Failed to parse game options JSON: {}", e);
+                    None => {
+                        error!("Failed to parse game options JSON.");
                     }
                 }
             } else {
@@ -131,3 +170,46 @@ impl OptionsRepository {
         false
     }
 }
+
+/// Copy of an options file as it was after the last successful save or load,
+/// used to recover from a damaged file.
+pub(crate) fn last_good_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".last-good");
+    path.with_file_name(name)
+}
+
+/// Replaces `path` with `data` so that a crash leaves either the old or the
+/// new file, never a truncated one: the data goes to a temporary file that is
+/// flushed to disk and then renamed over `path`.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+
+    let result = File::create(&temp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    if let Err(e) = result.and_then(|_| std::fs::rename(&temp, path)) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e);
+    }
+    options_watcher::remember(path, data);
+
+    // The rename itself only survives a power loss once the folder is synced
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let _ = File::open(dir).and_then(|d| d.sync_all());
+    }
+    Ok(())
+}
+
+/// Writes an options file and refreshes its last-known-good copy.
+fn write_options_file(path: &Path, json: &str) -> io::Result<()> {
+    write_atomic(path, json.as_bytes())?;
+    if let Err(e) = write_atomic(&last_good_path(path), json.as_bytes()) {
+        warn!("Could not update the last-known-good copy of {:?}: {}", path, e);
+    }
+    Ok(())
+}
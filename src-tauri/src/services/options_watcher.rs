@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use log::{info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::core::paths;
+use crate::models::options::{GameOptions, LauncherOptions};
+use crate::models::validation::FieldError;
+use crate::services::options_migration::{self, OptionsFile};
+
+/// How often the options files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Digest of the contents the launcher last wrote or reloaded, per file. A
+/// file that still matches it was not changed by anyone else.
+static KNOWN: Mutex<Option<HashMap<PathBuf, [u8; 32]>>> = Mutex::new(None);
+
+/// Sent with the `options-changed` event when an options file was edited
+/// outside the launcher. With `errors`, the edit was not applied.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsChanged {
+    pub file: String,
+    pub options: Option<LauncherOptions>,
+    pub game_options: Option<GameOptions>,
+    pub errors: Vec<FieldError>,
+}
+
+/// Records a write made by the launcher, so the watcher does not report it.
+pub(crate) fn remember(path: &Path, data: &[u8]) {
+    let digest: [u8; 32] = Sha256::digest(data).into();
+    KNOWN.lock().unwrap().get_or_insert_with(HashMap::new).insert(path.to_path_buf(), digest);
+}
+
+/// Returns `true` when `data` differs from what the launcher knows about `path`,
+/// and from then on treats it as known.
+fn is_new(path: &Path, data: &[u8]) -> bool {
+    let digest: [u8; 32] = Sha256::digest(data).into();
+    let mut known = KNOWN.lock().unwrap();
+    known.get_or_insert_with(HashMap::new).insert(path.to_path_buf(), digest) != Some(digest)
+}
+
+/// Watches `options.json` and `game_options.json` in the launcher directory.
+/// Edits made by hand or by the CLI are validated and sent to every window
+/// with an `options-changed` event.
+pub fn spawn(app: AppHandle) {
+    let Some(dir) = paths::launcher_dir() else {
+        info!("Launcher directory is not configured, options are not watched.");
+        return;
+    };
+    let files = [OptionsFile::Launcher, OptionsFile::Game];
+
+    tauri::async_runtime::spawn(async move {
+        // Whatever is on disk now is what the UI loads at startup
+        for file in files {
+            let path = dir.join(file.file_name());
+            if let Ok(data) = std::fs::read(&path) {
+                is_new(&path, &data);
+            }
+        }
+
+        let mut stamps: HashMap<&'static str, Option<(SystemTime, u64)>> =
+            files.iter().map(|f| (f.file_name(), stamp(&dir.join(f.file_name())))).collect();
+        let mut pending: Vec<&'static str> = Vec::new();
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+            for file in files {
+                let name = file.file_name();
+                let path = dir.join(name);
+                let current = stamp(&path);
+                if stamps.get(name) != Some(&current) {
+                    // Wait until the file stops changing, so a half-written one is not read
+                    stamps.insert(name, current);
+                    if !pending.contains(&name) {
+                        pending.push(name);
+                    }
+                    continue;
+                }
+                if !pending.contains(&name) {
+                    continue;
+                }
+                pending.retain(|p| *p != name);
+
+                let Ok(data) = std::fs::read(&path) else { continue };
+                if !is_new(&path, &data) {
+                    continue;
+                }
+                info!("{} was changed outside the launcher, reloading it.", name);
+                let change = reload(file, &path, &data);
+                if !change.errors.is_empty() {
+                    warn!("The edited {} was not applied: {:?}", name, change.errors);
+                }
+                let _ = app.emit("options-changed", change);
+            }
+        }
+    });
+}
+
+/// Parses and validates an edited file. Unlike a normal load, a damaged file
+/// is reported instead of being replaced, as the user may still be editing it.
+fn reload(file: OptionsFile, path: &Path, data: &[u8]) -> OptionsChanged {
+    let mut change = OptionsChanged {
+        file: file.file_name().to_string(),
+        options: None,
+        game_options: None,
+        errors: Vec::new(),
+    };
+    let data = String::from_utf8_lossy(data);
+    let result = match file {
+        OptionsFile::Launcher => parse::<LauncherOptions>(file, path, &data).map(|mut options| {
+            // Same fix-ups as `OptionsRepository::load_launcher_options`
+            options.launcher_dir = paths::launcher_dir();
+            options.game_dir = options.game_dir.as_deref().map(paths::from_stored);
+            options.validate().map(|_| change.options = Some(options))
+        }),
+        OptionsFile::Game => parse::<GameOptions>(file, path, &data).map(|options| {
+            options.validate().map(|_| change.game_options = Some(options))
+        }),
+    };
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(errors)) => change.errors = errors,
+        Err(message) => change.errors.push(FieldError::new("", message)),
+    }
+    change
+}
+
+fn parse<T: serde::de::DeserializeOwned>(file: OptionsFile, path: &Path, data: &str) -> Result<T, String> {
+    options_migration::parse::<T>(file, path, data)?
+        .ok_or_else(|| format!("{} could not be used by this launcher.", file.file_name()))
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
@@ -0,0 +1,145 @@
+use std::time::Instant;
+
+/// Resource usage of the game process tree at one point in time.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameMetrics {
+    /// Unix timestamp of the sample, in milliseconds.
+    pub timestamp: i64,
+    /// CPU usage since the previous sample; 100 means one full core.
+    pub cpu_percent: f64,
+    pub cpu_count: usize,
+    pub resident_memory_mb: u64,
+    /// The configured `max_ram`, so the UI can compare it with the real usage.
+    pub max_ram_mb: u32,
+    pub thread_count: u64,
+    pub process_count: usize,
+    pub uptime_secs: u64,
+}
+
+/// Samples a process and all of its descendants.
+/// CPU usage is computed from the difference with the previous sample, so the
+/// first sample always reports 0%.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct MetricsSampler {
+    root_pid: u32,
+    max_ram_mb: u32,
+    last_cpu: Option<(u64, Instant)>,
+}
+
+impl MetricsSampler {
+    pub fn new(root_pid: u32, max_ram_mb: u32) -> Self {
+        Self { root_pid, max_ram_mb, last_cpu: None }
+    }
+
+    /// Returns `None` once the root process is gone or on unsupported platforms.
+    #[cfg(target_os = "linux")]
+    pub fn sample(&mut self) -> Option<GameMetrics> {
+        let clk_tck = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+
+        let root = proc_stat(self.root_pid)?;
+        let tree = process_tree(self.root_pid);
+
+        let mut cpu_ticks = 0;
+        let mut rss_pages = 0;
+        let mut threads = 0;
+        for stat in tree.iter().filter_map(|pid| proc_stat(*pid)) {
+            cpu_ticks += stat.utime + stat.stime;
+            rss_pages += stat.rss_pages;
+            threads += stat.threads;
+        }
+
+        let now = Instant::now();
+        let cpu_percent = match self.last_cpu {
+            Some((last_ticks, last_at)) => {
+                let elapsed = now.duration_since(last_at).as_secs_f64();
+                if elapsed > 0.0 {
+                    cpu_ticks.saturating_sub(last_ticks) as f64 / clk_tck as f64 / elapsed * 100.0
+                } else {
+                    0.0
+                }
+            }
+            None => 0.0,
+        };
+        self.last_cpu = Some((cpu_ticks, now));
+
+        let system_uptime = std::fs::read_to_string("/proc/uptime")
+            .ok()
+            .and_then(|s| s.split_whitespace().next().and_then(|v| v.parse::<f64>().ok()))
+            .unwrap_or(0.0);
+        let uptime_secs = (system_uptime - root.start_ticks as f64 / clk_tck as f64).max(0.0) as u64;
+
+        Some(GameMetrics {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            cpu_percent: (cpu_percent * 10.0).round() / 10.0,
+            cpu_count: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            resident_memory_mb: rss_pages * page_size / (1024 * 1024),
+            max_ram_mb: self.max_ram_mb,
+            thread_count: threads,
+            process_count: tree.len(),
+            uptime_secs,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn sample(&mut self) -> Option<GameMetrics> {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// /proc helpers
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+pub(crate) struct ProcStat {
+    pub ppid: u32,
+    pub utime: u64,
+    pub stime: u64,
+    pub threads: u64,
+    pub start_ticks: u64,
+    pub rss_pages: u64,
+}
+
+/// Parses `/proc/<pid>/stat`. The command name may contain spaces and
+/// parentheses, so fields are counted from the last `)`.
+#[cfg(target_os = "linux")]
+pub(crate) fn proc_stat(pid: u32) -> Option<ProcStat> {
+    let raw = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let rest = &raw[raw.rfind(')')? + 1..];
+    // Index 0 is field 3 (state) of proc(5)
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3).and_then(|v| v.parse::<u64>().ok());
+
+    Some(ProcStat {
+        ppid: field(4)? as u32,
+        utime: field(14)?,
+        stime: field(15)?,
+        threads: field(20)?,
+        start_ticks: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// The PIDs of `root` and all of its descendants.
+#[cfg(target_os = "linux")]
+pub(crate) fn process_tree(root: u32) -> Vec<u32> {
+    let parents: Vec<(u32, u32)> = std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok())
+                .filter_map(|pid| proc_stat(pid).map(|s| (pid, s.ppid)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut tree = vec![root];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        tree.extend(parents.iter().filter(|(_, ppid)| *ppid == parent).map(|(pid, _)| *pid));
+        i += 1;
+    }
+    tree
+}
@@ -0,0 +1,154 @@
+use log::warn;
+#[cfg(target_os = "linux")]
+use log::info;
+
+use crate::models::options::ProcessPriority;
+#[cfg(target_os = "linux")]
+use crate::models::options::IoniceClass;
+
+/// Checks that the requested priority settings can be applied by the current user.
+///
+/// An unprivileged user may always lower the game's priority, but may only raise
+/// it as far as `RLIMIT_NICE` allows, cannot use the realtime I/O class, and can
+/// only pin the game to CPUs the launcher itself is allowed to run on.
+#[cfg(target_os = "linux")]
+pub fn validate(priority: &ProcessPriority) -> Result<(), String> {
+    let privileged = unsafe { libc::geteuid() } == 0;
+
+    if let Some(nice) = priority.niceness {
+        if !(-20..=19).contains(&nice) {
+            return Err(format!("Niceness must be between -20 and 19, got {}.", nice));
+        }
+        if !privileged {
+            let min_allowed = min_unprivileged_niceness();
+            if nice < min_allowed {
+                return Err(format!(
+                    "Niceness {} requires elevated privileges; the lowest value allowed for this user is {}.",
+                    nice, min_allowed
+                ));
+            }
+        }
+    }
+
+    if let Some(level) = priority.ionice_level {
+        if level > 7 {
+            return Err(format!("I/O priority level must be between 0 and 7, got {}.", level));
+        }
+    }
+
+    if priority.ionice_class == Some(IoniceClass::Realtime) && !privileged {
+        return Err("The realtime I/O class requires elevated privileges.".to_string());
+    }
+
+    if let Some(cpus) = &priority.cpu_affinity {
+        if cpus.is_empty() {
+            return Err("CPU affinity must contain at least one CPU.".to_string());
+        }
+        let allowed = allowed_cpus();
+        if let Some(cpu) = cpus.iter().find(|c| !allowed.contains(c)) {
+            return Err(format!(
+                "CPU {} is not available to the launcher (allowed: {:?}).",
+                cpu, allowed
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn validate(_priority: &ProcessPriority) -> Result<(), String> {
+    Ok(())
+}
+
+/// Configures `cmd` so the spawned process starts with the requested priority.
+/// The settings are applied between fork and exec, so every process the game
+/// starts afterwards inherits them.
+#[cfg(target_os = "linux")]
+pub fn apply(cmd: &mut tokio::process::Command, priority: &ProcessPriority) {
+    let niceness = priority.niceness;
+    let ioprio = priority.ionice_class.map(|class| {
+        let (class_id, level) = match class {
+            IoniceClass::Realtime => (1, priority.ionice_level.unwrap_or(4)),
+            IoniceClass::BestEffort => (2, priority.ionice_level.unwrap_or(4)),
+            IoniceClass::Idle => (3, 0),
+        };
+        (class_id << 13) | level as libc::c_int
+    });
+    let cpu_set = priority.cpu_affinity.as_ref().map(|cpus| {
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for &cpu in cpus {
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+        }
+        set
+    });
+
+    if niceness.is_none() && ioprio.is_none() && cpu_set.is_none() {
+        return;
+    }
+
+    info!(
+        "Applying process priority: niceness={:?} ioprio={:?} affinity={:?}",
+        niceness, ioprio, priority.cpu_affinity
+    );
+
+    // Only raw syscalls are made here, which is safe in the forked child
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(nice) = niceness {
+                if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(prio) = ioprio {
+                const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+                if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, prio) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(set) = &cpu_set {
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), set) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_cmd: &mut tokio::process::Command, priority: &ProcessPriority) {
+    if priority.niceness.is_some() || priority.ionice_class.is_some() || priority.cpu_affinity.is_some() {
+        warn!("Process priority settings are only supported on Linux and will be ignored.");
+    }
+}
+
+/// Lowest niceness an unprivileged process may set, derived from `RLIMIT_NICE`.
+/// Keeping the current niceness is always allowed.
+#[cfg(target_os = "linux")]
+fn min_unprivileged_niceness() -> i32 {
+    let current = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    let ceiling = if unsafe { libc::getrlimit(libc::RLIMIT_NICE, &mut limit) } == 0 {
+        20 - limit.rlim_cur.min(40) as i32
+    } else {
+        warn!("Could not read RLIMIT_NICE, assuming no priority raise is allowed");
+        current
+    };
+    ceiling.min(current)
+}
+
+/// CPUs the launcher process is currently allowed to run on.
+#[cfg(target_os = "linux")]
+fn allowed_cpus() -> Vec<usize> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } == 0;
+    if !ok {
+        warn!("Could not read the launcher CPU affinity, assuming all CPUs are available");
+        let count = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        return (0..count).collect();
+    }
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect()
+}
@@ -26,6 +26,18 @@ impl Default for ServerStatus {
     }
 }
 
+/// The server address from `MINECRAFT_SERVER_IP` / `MINECRAFT_SERVER_PORT`,
+/// defaulting to `localhost:25565`.
+pub fn configured_server() -> (String, u16) {
+    let host = std::env::var("MINECRAFT_SERVER_IP")
+        .unwrap_or_else(|_| "localhost".to_string());
+    let port: u16 = std::env::var("MINECRAFT_SERVER_PORT")
+        .unwrap_or_else(|_| "25565".to_string())
+        .parse()
+        .unwrap_or(25565);
+    (host, port)
+}
+
 /// Ping a Minecraft server using the Server List Ping (SLP) protocol.
 /// Returns a default offline status if the server is unreachable or times out.
 pub async fn get_server_status(host: &str, port: u16) -> ServerStatus {
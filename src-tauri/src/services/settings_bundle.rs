@@ -0,0 +1,215 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use chrono::{DateTime, Local};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::models::options::{GameOptions, LauncherOptions};
+use crate::models::validation::FieldError;
+use crate::services::options_migration::{self, OptionsFile};
+use crate::services::options_repository::OptionsRepository;
+
+/// Identifies a settings bundle, so an unrelated zip file is rejected early.
+const BUNDLE_FORMAT: &str = "permadeath-launcher-settings";
+const BUNDLE_VERSION: u32 = 1;
+
+const BUNDLE_ENTRY: &str = "bundle.json";
+
+/// Options files are a few KB; anything bigger is not a bundle we wrote.
+const MAX_ENTRY_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleInfo {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Local>,
+    launcher_version: String,
+}
+
+/// One setting that an import would change, by dotted path (`hooks.pre_launch.command`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChange {
+    /// `launcher` for `options.json`, `game` for `game_options.json`.
+    pub file: String,
+    pub field: String,
+    pub current: Value,
+    pub imported: Value,
+}
+
+/// What importing a bundle does, shown before it is applied.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsImport {
+    pub exported_at: DateTime<Local>,
+    pub launcher_version: String,
+    pub changes: Vec<SettingChange>,
+    /// Things worth a second look before applying, such as hooks that run programs.
+    pub warnings: Vec<String>,
+    pub applied: bool,
+}
+
+/// Writes the launcher and game options to a zip bundle at `path`.
+///
+/// The Microsoft token stays out of the bundle, and so does `launcher_dir`,
+/// which only makes sense on this computer.
+pub fn export_settings(launcher_opts: &LauncherOptions, path: &Path) -> Result<(), String> {
+    let game_opts = OptionsRepository::load_game_options(launcher_opts);
+
+    let mut exported = launcher_opts.clone();
+    exported.microsoft_token = None;
+    exported.launcher_dir = None;
+
+    let info = BundleInfo {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: Local::now(),
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    let write = || -> Result<(), Box<dyn std::error::Error>> {
+        let mut zip = ZipWriter::new(BufWriter::new(File::create(path)?));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(BUNDLE_ENTRY, options)?;
+        serde_json::to_writer_pretty(&mut zip, &info)?;
+        zip.start_file(OptionsFile::Launcher.file_name(), options)?;
+        serde_json::to_writer_pretty(&mut zip, &exported)?;
+        zip.start_file(OptionsFile::Game.file_name(), options)?;
+        serde_json::to_writer_pretty(&mut zip, &game_opts)?;
+        zip.finish()?.flush()?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        let _ = fs::remove_file(path);
+        return Err(format!("Could not export the settings: {}", e));
+    }
+
+    info!("Settings exported to {:?}", path);
+    Ok(())
+}
+
+/// Validates the bundle at `path` and lists what importing it would change.
+/// With `apply`, the imported options are also saved.
+///
+/// The local `launcher_dir` and Microsoft token are always kept.
+pub fn import_settings(launcher_opts: &LauncherOptions, path: &Path, apply: bool) -> Result<SettingsImport, String> {
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let mut archive = ZipArchive::new(BufReader::new(file))
+        .map_err(|_| format!("{} is not a settings bundle.", path.display()))?;
+
+    let info: BundleInfo = read_entry(&mut archive, BUNDLE_ENTRY)?;
+    if info.format != BUNDLE_FORMAT {
+        return Err(format!("{} is not a settings bundle.", path.display()));
+    }
+    if info.version > BUNDLE_VERSION {
+        return Err(format!(
+            "This bundle was exported by a newer launcher ({}). Update the launcher to import it.",
+            info.launcher_version
+        ));
+    }
+
+    let mut warnings = Vec::new();
+    let mut imported: LauncherOptions = read_options(&mut archive, OptionsFile::Launcher, &mut warnings)?;
+    imported.launcher_dir = launcher_opts.launcher_dir.clone();
+    imported.microsoft_token = launcher_opts.microsoft_token.clone();
+    let imported_game: GameOptions = read_options(&mut archive, OptionsFile::Game, &mut warnings)?;
+    let current_game = OptionsRepository::load_game_options(launcher_opts);
+
+    let mut changes = Vec::new();
+    diff("launcher", "", &to_value(launcher_opts)?, &to_value(&imported)?, &mut changes);
+    diff("game", "", &to_value(&current_game)?, &to_value(&imported_game)?, &mut changes);
+
+    if changes.iter().any(|c| c.file == "launcher" && c.field.starts_with("hooks.")) {
+        warnings.push("The bundle changes the launch hooks, which run programs on this computer.".to_string());
+    }
+    if changes.iter().any(|c| c.file == "launcher" && c.field == "game_dir") {
+        warnings.push("The bundle points the game to a different folder.".to_string());
+    }
+
+    if apply {
+        let invalid = |errors: Vec<FieldError>| {
+            let details: Vec<String> = errors.iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+            format!("The bundle has invalid settings: {}", details.join(" "))
+        };
+        imported.validate().map_err(invalid)?;
+        imported_game.validate().map_err(invalid)?;
+        OptionsRepository::save_launcher_options(&imported)?;
+        OptionsRepository::save_game_options(&imported_game, &imported)?;
+        info!("Imported {} setting change(s) from {:?}", changes.len(), path);
+    }
+
+    Ok(SettingsImport {
+        exported_at: info.exported_at,
+        launcher_version: info.launcher_version,
+        changes,
+        warnings,
+        applied: apply,
+    })
+}
+
+fn read_entry<T: serde::de::DeserializeOwned>(archive: &mut ZipArchive<BufReader<File>>, name: &str) -> Result<T, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| format!("The bundle is incomplete: {} is missing.", name))?;
+    let mut data = String::new();
+    entry
+        .take(MAX_ENTRY_SIZE)
+        .read_to_string(&mut data)
+        .map_err(|e| format!("Could not read {} from the bundle: {}", name, e))?;
+    serde_json::from_str(&data).map_err(|e| format!("{} in the bundle is invalid: {}", name, e))
+}
+
+/// Reads an options entry and upgrades it to the current schema, as loading
+/// the file from the launcher directory would.
+fn read_options<T: serde::de::DeserializeOwned>(
+    archive: &mut ZipArchive<BufReader<File>>,
+    file: OptionsFile,
+    warnings: &mut Vec<String>,
+) -> Result<T, String> {
+    let name = file.file_name();
+    let mut value: Value = read_entry(archive, name)?;
+    let version = options_migration::migrate(file, &mut value)
+        .map_err(|e| format!("{} in the bundle could not be upgraded: {}", name, e))?;
+    if version > file.current_version() {
+        warnings.push(format!(
+            "{} was written by a newer launcher; settings this launcher does not know are left out.",
+            name
+        ));
+    }
+    serde_json::from_value(value).map_err(|e| format!("{} in the bundle is invalid: {}", name, e))
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+/// Collects the leaves that differ between two JSON trees. Arrays are compared whole.
+fn diff(file: &str, prefix: &str, current: &Value, imported: &Value, out: &mut Vec<SettingChange>) {
+    if let (Value::Object(a), Value::Object(b)) = (current, imported) {
+        let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let field = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            diff(
+                file,
+                &field,
+                a.get(key).unwrap_or(&Value::Null),
+                b.get(key).unwrap_or(&Value::Null),
+                out,
+            );
+        }
+    } else if current != imported {
+        out.push(SettingChange {
+            file: file.to_string(),
+            field: prefix.to_string(),
+            current: current.clone(),
+            imported: imported.clone(),
+        });
+    }
+}
@@ -3,9 +3,18 @@ import { CommonModule } from '@angular/common';
 import { Router, RouterOutlet } from '@angular/router';
 import { TauriService } from './services/tauri.service';
 import { AuthService } from './services/auth.service';
+import { DeepLinkService } from './services/deep-link.service';
 import { SidebarComponent } from '@app/components/sidebar/sidebar.component';
 import { FooterComponent } from '@app/components/footer/footer.component';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { invoke } from '@tauri-apps/api/core';
+import { message } from '@tauri-apps/plugin-dialog';
+
+interface OptionsNotice {
+  file: string;
+  message: string;
+  backup: string | null;
+}
 
 @Component({
   selector: 'app-root',
@@ -37,6 +46,7 @@ export class AppComponent implements OnInit {
   constructor(
     private tauri: TauriService,
     private auth: AuthService,
+    private deepLink: DeepLinkService,
     private router: Router,
   ) {}
 
@@ -49,9 +59,20 @@ export class AppComponent implements OnInit {
       await this.router.navigate(['/console']);
     } else {
       await this.tauri.initOptions();
+      await this.showOptionsNotices();
       await this.auth.checkAutoLogin();
+      await this.deepLink.init();
     }
 
     this.isLoaded = true;
   }
+
+  /** Tells the user when an options file was upgraded badly, unreadable or from a newer launcher. */
+  private async showOptionsNotices() {
+    const notices = await invoke<OptionsNotice[]>('take_options_notices');
+    for (const notice of notices) {
+      const backup = notice.backup ? `\n\nThe original file was kept at:\n${notice.backup}` : '';
+      await message(notice.message + backup, { title: 'Settings', kind: 'warning' });
+    }
+  }
 }
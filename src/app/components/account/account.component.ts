@@ -1,11 +1,12 @@
-import { Component, ChangeDetectorRef, NgZone } from '@angular/core';
+import { Component, ChangeDetectorRef, NgZone, OnDestroy } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { AuthService } from '@app/services/auth.service';
+import { DeepLinkService } from '@app/services/deep-link.service';
 import { invoke } from '@tauri-apps/api/core';
 import { message } from '@tauri-apps/plugin-dialog';
 import { LucideAngularModule, LogIn, User, TriangleAlert, LogOut, ShieldCheck, UserRound } from 'lucide-angular';
-import { Observable } from 'rxjs';
+import { Observable, Subscription } from 'rxjs';
 import {UserData} from "@app/models/userdata";
 
 @Component({
@@ -15,7 +16,7 @@ import {UserData} from "@app/models/userdata";
   templateUrl: './account.component.html',
   styleUrls: ['./account.component.css']
 })
-export class AccountComponent {
+export class AccountComponent implements OnDestroy {
   readonly LogIn = LogIn;
   readonly User = User;
   readonly TriangleAlert = TriangleAlert;
@@ -33,13 +34,29 @@ export class AccountComponent {
   showRegisterModal = false;
 
   user$: Observable<any>;
+  private linkSub: Subscription;
 
   constructor(
     public auth: AuthService,
     private cdr: ChangeDetectorRef,
-    private ngZone: NgZone
+    private ngZone: NgZone,
+    deepLink: DeepLinkService,
   ) {
     this.user$ = this.auth.user$;
+
+    // Invitation codes confirmed from a permadeath://redeem link
+    this.linkSub = deepLink.inviteCode$.subscribe(code => {
+      if (!code) return;
+      deepLink.inviteCode$.next(null);
+      this.ngZone.run(() => {
+        this.regCode = code;
+        this.openModal();
+      });
+    });
+  }
+
+  ngOnDestroy(): void {
+    this.linkSub.unsubscribe();
   }
 
   async onLogin() {
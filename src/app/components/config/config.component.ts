@@ -2,9 +2,52 @@ import { Component } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { TauriService } from '../../services/tauri.service';
-import { message, open } from '@tauri-apps/plugin-dialog';
+import { ask, message, open, save } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { LucideAngularModule, Settings, Gamepad2 } from 'lucide-angular';
+import { describeOptionsError, OptionsError } from '@app/models/options';
+
+interface SettingChange {
+  file: 'launcher' | 'game';
+  field: string;
+  current: unknown;
+  imported: unknown;
+}
+
+interface SettingsImport {
+  exportedAt: string;
+  launcherVersion: string;
+  changes: SettingChange[];
+  warnings: string[];
+  applied: boolean;
+}
+
+interface MoveProgress {
+  phase: 'copying' | 'verifying' | 'removingSource' | 'rollingBack';
+  filesDone: number;
+  filesTotal: number;
+  bytesDone: number;
+  bytesTotal: number;
+}
+
+interface MoveOutcome {
+  gameDir: string;
+  files: number;
+  bytes: number;
+  sourceKept: boolean;
+  warnings: string[];
+}
+
+const MOVE_PHASES: Record<MoveProgress['phase'], string> = {
+  copying: 'Copying',
+  verifying: 'Verifying',
+  removingSource: 'Removing the old folder',
+  rollingBack: 'Undoing',
+};
+
+/** Changes listed in the import confirmation before the rest are summarised. */
+const MAX_LISTED_CHANGES = 12;
 
 @Component({
   selector: 'app-config',
@@ -17,21 +60,62 @@ export class ConfigComponent {
   readonly Settings = Settings;
   readonly Gamepad2 = Gamepad2;
 
+  /** Validation messages by option path, shown under the matching input. */
+  fieldErrors: Record<string, string> = {};
+
+  /** Shown under the game directory while its files are moved. */
+  moveStatus: string | null = null;
+
   constructor(public tauri: TauriService) {}
 
   async changeGameDir() {
     const gameDir = await open({ multiple: false, directory: true });
-    if (gameDir && this.tauri.options) {
+    if (!gameDir || !this.tauri.options) return;
+
+    const move = await ask(
+      'Move your worlds, mods and settings to the new folder?\n\n' +
+      'Choose "Only change path" if the folder already has a game installed.',
+      { title: 'Game Directory', kind: 'info', okLabel: 'Move files', cancelLabel: 'Only change path' }
+    );
+    if (!move) {
       this.tauri.options.game_dir = gameDir as string;
+      delete this.fieldErrors['game_dir'];
+      return;
+    }
+    await this.moveGameDir(gameDir as string);
+  }
+
+  /** Moves the game files and saves the new path; on failure nothing changes. */
+  private async moveGameDir(target: string) {
+    const unlisten = await listen<MoveProgress>('game-dir-move-progress', e => {
+      const p = e.payload;
+      const percent = p.phase === 'copying'
+        ? Math.floor((p.bytesDone / Math.max(p.bytesTotal, 1)) * 100)
+        : Math.floor((p.filesDone / Math.max(p.filesTotal, 1)) * 100);
+      this.moveStatus = `${MOVE_PHASES[p.phase]}… ${percent}%`;
+    });
+    try {
+      this.moveStatus = 'Preparing…';
+      const outcome = await invoke<MoveOutcome>('move_game_dir', { target, keepSource: false });
+      if (this.tauri.options) this.tauri.options.game_dir = outcome.gameDir;
+      delete this.fieldErrors['game_dir'];
+      const notes = outcome.warnings.length > 0 ? `\n\n${outcome.warnings.join('\n')}` : '';
+      await message(`Moved ${outcome.files} files to ${outcome.gameDir}.${notes}`, { title: 'Game Directory', kind: 'info' });
+    } catch (error) {
+      await message(String(error), { title: 'Game Directory', kind: 'error' });
+    } finally {
+      unlisten();
+      this.moveStatus = null;
     }
   }
 
   async saveConfig() {
-    const status = await this.tauri.saveOptions();
-    if (status) {
+    const error = await this.tauri.saveOptions();
+    if (!error) {
+      this.fieldErrors = {};
       await message('Options saved successfully', { title: 'Save Options', kind: 'info' });
     } else {
-      await message('Failed to save options.', { title: 'Save Options', kind: 'error' });
+      await this.showSaveError(error, 'Save Options');
     }
   }
 
@@ -43,12 +127,75 @@ export class ConfigComponent {
       this.tauri.options.automatic_backup = true;
       this.tauri.options.close_on_launch = false;
       this.tauri.options.game_dir = gameDir;
-      const status = await this.tauri.saveOptions();
-      if (status) {
+      const error = await this.tauri.saveOptions();
+      if (!error) {
+        this.fieldErrors = {};
         await message('Options reset successfully', { title: 'Reset Options', kind: 'info' });
       } else {
-        await message('Failed to reset options.', { title: 'Reset Options', kind: 'error' });
+        await this.showSaveError(error, 'Reset Options');
       }
     }
   }
-}
\ No newline at end of file
+
+  /** Field errors this page has an input for go next to it; the rest go in a dialog. */
+  private async showSaveError(error: OptionsError, title: string) {
+    this.fieldErrors = {};
+    if (error.kind === 'invalid') {
+      const unshown = error.errors.filter(e => {
+        if (e.field !== 'game_dir') return true;
+        this.fieldErrors[e.field] ??= e.message;
+        return false;
+      });
+      if (unshown.length === 0) return;
+      error = { kind: 'invalid', errors: unshown };
+    }
+    await message(describeOptionsError(error), { title, kind: 'error' });
+  }
+
+  async exportSettings() {
+    const path = await save({
+      defaultPath: 'permadeath-settings.zip',
+      filters: [{ name: 'Settings bundle', extensions: ['zip'] }],
+    });
+    if (!path) return;
+    try {
+      await invoke('export_settings', { path });
+      await message('Settings exported. The Microsoft login is not included.', { title: 'Export Settings', kind: 'info' });
+    } catch (error) {
+      await message(String(error), { title: 'Export Settings', kind: 'error' });
+    }
+  }
+
+  async importSettings() {
+    const path = await open({
+      multiple: false,
+      filters: [{ name: 'Settings bundle', extensions: ['zip'] }],
+    });
+    if (!path) return;
+    try {
+      const preview = await invoke<SettingsImport>('import_settings', { path, apply: false });
+      if (preview.changes.length === 0) {
+        await message('These settings are the same as the current ones.', { title: 'Import Settings', kind: 'info' });
+        return;
+      }
+
+      const listed = preview.changes
+        .slice(0, MAX_LISTED_CHANGES)
+        .map(c => `• ${c.field}: ${JSON.stringify(c.current)} → ${JSON.stringify(c.imported)}`);
+      if (preview.changes.length > MAX_LISTED_CHANGES) {
+        listed.push(`…and ${preview.changes.length - MAX_LISTED_CHANGES} more`);
+      }
+      const confirmed = await ask(
+        [...preview.warnings, ...listed, '', 'Apply these changes?'].join('\n'),
+        { title: 'Import Settings', kind: preview.warnings.length > 0 ? 'warning' : 'info' }
+      );
+      if (!confirmed) return;
+
+      await invoke<SettingsImport>('import_settings', { path, apply: true });
+      await this.tauri.initOptions();
+      await message('Settings imported.', { title: 'Import Settings', kind: 'info' });
+    } catch (error) {
+      await message(String(error), { title: 'Import Settings', kind: 'error' });
+    }
+  }
+}
@@ -2,17 +2,67 @@ import {
   Component, OnInit, OnDestroy, NgZone, ViewChild, ElementRef, AfterViewChecked
 } from '@angular/core';
 import { CommonModule } from '@angular/common';
+import { FormsModule } from '@angular/forms';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
-import { LucideAngularModule, Terminal, Square, Skull } from 'lucide-angular';
+import { message } from '@tauri-apps/plugin-dialog';
+import { LucideAngularModule, Terminal, Square, Skull, Share2 } from 'lucide-angular';
 import { GameState } from '@app/components/play/play.component';
 
-interface LogLine { text: string; isError: boolean; }
+type LogLevel = 'TRACE' | 'DEBUG' | 'INFO' | 'WARN' | 'ERROR' | 'FATAL';
+
+const LEVELS: LogLevel[] = ['TRACE', 'DEBUG', 'INFO', 'WARN', 'ERROR', 'FATAL'];
+
+/** One parsed entry of the game output (`LogRecord` in the backend). */
+interface LogRecord {
+  time: string | null;
+  thread: string | null;
+  level: LogLevel | null;
+  logger: string | null;
+  message: string;
+  isError: boolean;
+}
+
+/** Messages from the launcher itself are listed under this source. */
+const LAUNCHER_SOURCE = 'launcher';
+
+/** A known problem detected in the game output (`GameDiagnosis` in the backend). */
+interface GameDiagnosis {
+  ruleId: string;
+  title: string;
+  explanation: string;
+  suggestion: string;
+  matchedLine: string;
+  source: 'game_log' | 'crash_report';
+  crashReport: string | null;
+}
+
+type DiagnosticKind = 'thread_dump' | 'heap_info' | 'class_histogram';
+
+/** Output of a `jcmd` capture, also saved in the session folder. */
+interface DiagnosticCapture {
+  kind: DiagnosticKind;
+  pid: number;
+  path: string;
+  output: string;
+}
+
+/** Resource sample of the game process tree (`GameMetrics` in the backend). */
+interface GameMetrics {
+  timestamp: number;
+  cpuPercent: number;
+  cpuCount: number;
+  residentMemoryMb: number;
+  maxRamMb: number;
+  threadCount: number;
+  processCount: number;
+  uptimeSecs: number;
+}
 
 @Component({
   selector: 'app-console-window',
   standalone: true,
-  imports: [CommonModule, LucideAngularModule],
+  imports: [CommonModule, FormsModule, LucideAngularModule],
   templateUrl: './console-window.component.html',
   styleUrls: ['./console-window.component.css'],
 })
@@ -20,15 +70,38 @@ export class ConsoleWindowComponent implements OnInit, OnDestroy, AfterViewCheck
   readonly Terminal = Terminal;
   readonly Square   = Square;
   readonly Skull    = Skull;
+  readonly Share2   = Share2;
 
   gameState: GameState = 'running';
-  logLines: LogLine[] = [];
+  logLines: LogRecord[] = [];
+  sources: string[] = [];
+  readonly levels = LEVELS;
+  /** Lowest level shown; '' shows everything. */
+  minLevel: LogLevel | '' = '';
+  /** Logger to show, or `LAUNCHER_SOURCE`; '' shows every source. */
+  source = '';
+  readonly launcherSource = LAUNCHER_SOURCE;
   exitCode: number | null = null;
+  sharing = false;
+  sharedUrl: string | null = null;
+  metrics: GameMetrics | null = null;
+  diagnoses: GameDiagnosis[] = [];
+  capturing: DiagnosticKind | null = null;
+  capture: DiagnosticCapture | null = null;
+
+  readonly diagnostics: { kind: DiagnosticKind; label: string }[] = [
+    { kind: 'thread_dump',     label: 'Thread dump' },
+    { kind: 'heap_info',       label: 'Heap info' },
+    { kind: 'class_histogram', label: 'Class histogram' },
+  ];
   private shouldScroll = false;
 
   private unlistenState?:   UnlistenFn;
   private unlistenLog?:     UnlistenFn;
+  private unlistenRecord?:  UnlistenFn;
   private unlistenExited?:  UnlistenFn;
+  private unlistenMetrics?: UnlistenFn;
+  private unlistenDiagnosis?: UnlistenFn;
 
   @ViewChild('consoleEl') private consoleEl?: ElementRef<HTMLDivElement>;
 
@@ -36,23 +109,38 @@ export class ConsoleWindowComponent implements OnInit, OnDestroy, AfterViewCheck
 
   async ngOnInit(): Promise<void> {
     this.gameState = await invoke<GameState>('get_game_state');
+    this.metrics = await invoke<GameMetrics | null>('get_game_metrics');
 
     this.unlistenState = await listen<GameState>('game-state', e => {
       this.ngZone.run(() => { this.gameState = e.payload; });
     });
 
-    this.unlistenLog = await listen<{ line: string; isError: boolean }>('game-log', e => {
-      this.ngZone.run(() => {
-        this.logLines.push({ text: e.payload.line, isError: e.payload.isError });
-        if (this.logLines.length > 800) this.logLines.shift();
-        this.shouldScroll = true;
-      });
+    // Game output arrives as records; plain lines are only the launcher's own
+    this.unlistenLog = await listen<{ line: string; isError: boolean; fromGame: boolean }>('game-log', e => {
+      if (e.payload.fromGame) return;
+      this.ngZone.run(() => this.addRecord({
+        time: null, thread: null, level: null, logger: LAUNCHER_SOURCE,
+        message: e.payload.line, isError: e.payload.isError,
+      }));
+    });
+
+    this.unlistenRecord = await listen<LogRecord>('game-log-record', e => {
+      this.ngZone.run(() => this.addRecord(e.payload));
+    });
+
+    this.unlistenMetrics = await listen<GameMetrics>('game-metrics', e => {
+      this.ngZone.run(() => { this.metrics = e.payload; });
+    });
+
+    this.unlistenDiagnosis = await listen<GameDiagnosis>('game-diagnosis', e => {
+      this.ngZone.run(() => { this.diagnoses.push(e.payload); });
     });
 
     this.unlistenExited = await listen<number>('game-exited', e => {
       this.ngZone.run(() => {
         this.exitCode = e.payload;
         this.gameState = 'idle';
+        this.metrics = null;
       });
     });
   }
@@ -60,7 +148,10 @@ export class ConsoleWindowComponent implements OnInit, OnDestroy, AfterViewCheck
   ngOnDestroy(): void {
     this.unlistenState?.();
     this.unlistenLog?.();
+    this.unlistenRecord?.();
     this.unlistenExited?.();
+    this.unlistenMetrics?.();
+    this.unlistenDiagnosis?.();
   }
 
   ngAfterViewChecked(): void {
@@ -75,7 +166,87 @@ export class ConsoleWindowComponent implements OnInit, OnDestroy, AfterViewCheck
     await invoke('stop_game');
   }
 
-  clearLog(): void { this.logLines = []; }
+  /** Uploads the redacted log of this session; the backend also copies the URL. */
+  async shareLog(): Promise<void> {
+    this.sharing = true;
+    try {
+      this.sharedUrl = await invoke<string>('share_game_log');
+    } catch (err: any) {
+      await message(String(err), { title: 'Share Log', kind: 'error' });
+    } finally {
+      this.sharing = false;
+    }
+  }
+
+  async captureDiagnostic(kind: DiagnosticKind): Promise<void> {
+    this.capturing = kind;
+    try {
+      this.capture = await invoke<DiagnosticCapture>('capture_game_diagnostic', { kind });
+    } catch (err: any) {
+      await message(String(err), { title: 'Capture Error', kind: 'error' });
+    } finally {
+      this.capturing = null;
+    }
+  }
+
+  captureLabel(kind: DiagnosticKind): string {
+    return this.diagnostics.find(d => d.kind === kind)?.label ?? kind;
+  }
+
+  dismissDiagnosis(diagnosis: GameDiagnosis): void {
+    this.diagnoses = this.diagnoses.filter(d => d !== diagnosis);
+  }
+
+  private addRecord(record: LogRecord): void {
+    this.logLines.push(record);
+    if (this.logLines.length > 800) this.logLines.shift();
+    if (record.logger && !this.sources.includes(record.logger)) {
+      this.sources = [...this.sources, record.logger].sort();
+    }
+    this.shouldScroll = true;
+  }
+
+  get visibleLines(): LogRecord[] {
+    const min = this.minLevel ? LEVELS.indexOf(this.minLevel) : -1;
+    return this.logLines.filter(r =>
+      (!this.source || r.logger === this.source) &&
+      (min < 0 || LEVELS.indexOf(this.levelOf(r)) >= min));
+  }
+
+  /** Lines without a level (plain prints) count as INFO, or ERROR on stderr. */
+  levelOf(record: LogRecord): LogLevel {
+    return record.level ?? (record.isError ? 'ERROR' : 'INFO');
+  }
+
+  lineClass(record: LogRecord): string {
+    switch (this.levelOf(record)) {
+      case 'FATAL':
+      case 'ERROR': return 'text-[#f85149]';
+      case 'WARN':  return 'text-amber-400';
+      case 'DEBUG':
+      case 'TRACE': return 'text-[#8b949e]';
+      default:      return 'text-[#c9d1d9]';
+    }
+  }
+
+  clearLog(): void { this.logLines = []; this.sources = []; this.source = ''; }
 
   get isRunning(): boolean { return this.gameState === 'running'; }
+
+  /** Share of `max_ram` the game process tree uses, in percent. */
+  get memoryPercent(): number {
+    if (!this.metrics || this.metrics.maxRamMb === 0) return 0;
+    return Math.round(this.metrics.residentMemoryMb / this.metrics.maxRamMb * 100);
+  }
+
+  /** The game uses (almost) all of `max_ram`, so more memory may help. */
+  get nearMaxRam(): boolean { return this.memoryPercent >= 90; }
+
+  get uptime(): string {
+    const secs = this.metrics?.uptimeSecs ?? 0;
+    const h = Math.floor(secs / 3600);
+    const m = Math.floor(secs % 3600 / 60);
+    const s = secs % 60;
+    return h > 0 ? `${h}h ${m}m` : `${m}m ${s}s`;
+  }
 }
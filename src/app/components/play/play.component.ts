@@ -12,9 +12,10 @@ import {
 } from 'lucide-angular';
 import { AuthService } from '@app/services/auth.service';
 import { TauriService } from '@app/services/tauri.service';
+import { DeepLinkService } from '@app/services/deep-link.service';
 import { UserData } from '@app/models/userdata';
 
-export type GameState = 'idle' | 'downloading' | 'running';
+export type GameState = 'idle' | 'preparing' | 'downloading' | 'running';
 
 interface LogLine { text: string; isError: boolean; }
 
@@ -59,6 +60,7 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
   private shouldScrollLog = false;
   private statusInterval?: ReturnType<typeof setInterval>;
   private userSub?: Subscription;
+  private linkSub?: Subscription;
   private unlistenState?:   UnlistenFn;
   private unlistenLog?:     UnlistenFn;
   private unlistenExited?:  UnlistenFn;
@@ -68,6 +70,7 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
   constructor(
     public auth:  AuthService,
     public tauri: TauriService,
+    private deepLink: DeepLinkService,
     private ngZone: NgZone,
   ) {}
 
@@ -84,6 +87,7 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
 
   ngOnDestroy(): void {
     this.userSub?.unsubscribe();
+    this.linkSub?.unsubscribe();
     this.unlistenState?.();
     this.unlistenLog?.();
     this.unlistenExited?.();
@@ -131,6 +135,13 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
     if (this.tauri.options?.init_on_start && this.currentUser && this.gameState === 'idle') {
       await this.launchGame();
     }
+
+    // Launches confirmed from a permadeath:// link
+    this.linkSub = this.deepLink.launch$.subscribe(request => {
+      if (!request) return;
+      this.deepLink.launch$.next(null);
+      this.ngZone.run(() => this.launchFromLink(request.offline));
+    });
   }
 
   // -------------------------------------------------------------------------
@@ -167,13 +178,14 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
   // Game controls
   // -------------------------------------------------------------------------
 
-  async launchGame(): Promise<void> {
+  async launchGame(offline = false): Promise<void> {
     if (!this.currentUser) return;
 
-    const hasToken = await invoke<boolean>('check_ms_auth_state');
-    let useMicrosoft: boolean | null = hasToken ? true : null;
+    // Offline launches from a link skip the Microsoft account question
+    const hasToken = !offline && await invoke<boolean>('check_ms_auth_state');
+    let useMicrosoft: boolean | null = offline ? false : hasToken ? true : null;
 
-    if (!hasToken) {
+    if (useMicrosoft === null) {
       useMicrosoft = await this.askMicrosoftAuth();
 
       // Si el usuario cerró el modal pulsando fuera (null), DETENEMOS LA FUNCIÓN AQUÍ.
@@ -192,6 +204,18 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
     }
   }
 
+  private async launchFromLink(offline: boolean): Promise<void> {
+    if (!this.currentUser) {
+      await message('Log in to your launcher account first, then open the link again.', { title: 'Join now', kind: 'warning' });
+      return;
+    }
+    if (!this.isIdle) {
+      await message('The game is already running.', { title: 'Join now', kind: 'info' });
+      return;
+    }
+    await this.launchGame(offline);
+  }
+
   async stopGame(): Promise<void> {
     try {
       await invoke('stop_game');
@@ -211,5 +235,6 @@ export class PlayComponent implements OnInit, OnDestroy, AfterViewChecked {
   get isIdle():           boolean { return this.gameState === 'idle'; }
   get isRunning():        boolean { return this.gameState === 'running'; }
   get isDownloading():    boolean { return this.gameState === 'downloading'; }
+  get isPreparing():      boolean { return this.gameState === 'preparing'; }
   get canLaunch():        boolean { return this.isLoggedIn && this.isIdle; }
 }
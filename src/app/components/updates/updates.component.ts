@@ -2,6 +2,7 @@ import { Component } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { TauriService } from '@app/services/tauri.service';
 import { message } from '@tauri-apps/plugin-dialog';
+import { describeOptionsError } from '@app/models/options';
 import { LucideAngularModule, Download, RefreshCw, AlertCircle } from 'lucide-angular';
 
 @Component({
@@ -33,7 +34,11 @@ export class UpdatesComponent {
     }
 
     private async save() {
-        const status = await this.tauri.saveOptions();
-        if (status) await message('Saved successfully', { title: 'Success', kind: 'info' });
+        const error = await this.tauri.saveOptions();
+        if (error) {
+            await message(describeOptionsError(error), { title: 'Save Options', kind: 'error' });
+        } else {
+            await message('Saved successfully', { title: 'Success', kind: 'info' });
+        }
     }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-import { Component, OnInit, NgZone } from '@angular/core';
+import { Component, OnInit, OnDestroy, NgZone } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { FormsModule } from '@angular/forms';
 import { TauriService } from '@app/services/tauri.service';
@@ -6,6 +6,8 @@ import { invoke } from '@tauri-apps/api/core';
 import { message } from '@tauri-apps/plugin-dialog';
 import { LucideAngularModule, Zap, Info, MemoryStick, Gauge, TriangleAlert } from 'lucide-angular';
 import { JavaVersions } from '@app/models/java-versions.enum';
+import { describeOptionsError, FlagCategory, FlagInfo, JvmPreset, OptionsError } from '@app/models/options';
+import { Subscription } from 'rxjs';
 
 @Component({
   selector: 'app-vm',
@@ -14,7 +16,7 @@ import { JavaVersions } from '@app/models/java-versions.enum';
   templateUrl: './vm.component.html',
   styleUrls: ['./vm.component.css']
 })
-export class VmComponent implements OnInit {
+export class VmComponent implements OnInit, OnDestroy {
   readonly Zap = Zap;
   readonly Info = Info;
   readonly MemoryStick = MemoryStick;
@@ -22,20 +24,75 @@ export class VmComponent implements OnInit {
   readonly TriangleAlert = TriangleAlert;
 
   gcOptions: string[] = [];
+  presets: JvmPreset[] = [];
+  selectedPreset = '';
+
+  /** The flags in the text box grouped by category, for the summary under it. */
+  flagGroups: { label: string; flags: FlagInfo[] }[] = [];
   javaVersions = Object.values(JavaVersions);
 
   maxRamStr = '4096MB';
   jvmArgsStr = '';
 
+  /** Validation messages by input: `max_ram`, `vm_flags` (all flags) and `custom_java_path`. */
+  fieldErrors: Record<string, string[]> = {};
+
+  private reloaded?: Subscription;
+
   constructor(public tauri: TauriService, private ngZone: NgZone) {}
 
   async ngOnInit() {
     this.gcOptions = await invoke<string[]>('get_garbage_collectors');
-    if (this.tauri.gameOptions) {
-      this.maxRamStr = `${this.tauri.gameOptions.max_ram}MB`;
-      this.jvmArgsStr = this.tauri.gameOptions.vm_flags.join(' ');
-    }
-    this.ngZone.run(() => {});
+    this.presets = await invoke<JvmPreset[]>('get_jvm_presets');
+    this.loadInputs();
+    this.reloaded = this.tauri.optionsReloaded.subscribe(() => this.loadInputs());
+  }
+
+  ngOnDestroy() {
+    this.reloaded?.unsubscribe();
+  }
+
+  private loadInputs() {
+    this.ngZone.run(() => {
+      if (this.tauri.gameOptions) {
+        this.maxRamStr = `${this.tauri.gameOptions.max_ram}MB`;
+        this.jvmArgsStr = this.tauri.gameOptions.vm_flags.join(' ');
+      }
+      this.fieldErrors = {};
+    });
+    void this.describeFlags();
+  }
+
+  /** Fills the memory, collector and flags from a preset; nothing is saved until Apply. */
+  applyPreset(id: string) {
+    const preset = this.presets.find(p => p.id === id);
+    if (!preset || !this.tauri.gameOptions) return;
+    this.tauri.gameOptions.garbage_collector = preset.garbage_collector;
+    if (preset.max_ram) this.maxRamStr = `${preset.max_ram}MB`;
+    this.jvmArgsStr = preset.flags.join(' ');
+    this.fieldErrors = {};
+    void this.describeFlags();
+  }
+
+  /** Groups the typed flags by category and marks those the chosen collector ignores. */
+  async describeFlags() {
+    if (!this.tauri.gameOptions) return;
+    const gameOptions = { ...this.tauri.gameOptions, vm_flags: this.parseFlags() };
+    const infos = await invoke<FlagInfo[]>('describe_jvm_flags', { gameOptions });
+    const labels: Record<FlagCategory, string> = {
+      memory: 'Memory',
+      gc: 'Garbage collector',
+      diagnostics: 'Diagnostics',
+      system_property: 'System properties',
+      other: 'Other',
+    };
+    this.flagGroups = (Object.keys(labels) as FlagCategory[])
+      .map(category => ({ label: labels[category], flags: infos.filter(i => i.category === category) }))
+      .filter(group => group.flags.length > 0);
+  }
+
+  private parseFlags(): string[] {
+    return this.jvmArgsStr.split(' ').filter(f => f.trim() !== '');
   }
 
   /** Persists all JVM options including GC and Java version */
@@ -49,13 +106,14 @@ export class VmComponent implements OnInit {
     }
 
     this.tauri.gameOptions.max_ram = maxRam;
-    this.tauri.gameOptions.vm_flags = this.jvmArgsStr.split(' ').filter(f => f.trim() !== '');
+    this.tauri.gameOptions.vm_flags = this.parseFlags();
 
-    const status = await this.tauri.saveGameOptions();
-    if (status) {
+    const error = await this.tauri.saveGameOptions();
+    if (!error) {
+      this.fieldErrors = {};
       await message('Game options saved successfully', { title: 'Save Game Options', kind: 'info' });
     } else {
-      await message('Failed to save game options.', { title: 'Save Game Options', kind: 'error' });
+      await this.showSaveError(error, 'Save Game Options');
     }
   }
 
@@ -64,12 +122,31 @@ export class VmComponent implements OnInit {
     this.jvmArgsStr = defaultFlags.join(' ');
     if (this.tauri.gameOptions) {
       this.tauri.gameOptions.vm_flags = defaultFlags;
-      const status = await this.tauri.saveGameOptions();
-      if (status) {
+      this.selectedPreset = '';
+      void this.describeFlags();
+      const error = await this.tauri.saveGameOptions();
+      if (!error) {
+        this.fieldErrors = {};
         await message('Game options reset successfully', { title: 'Reset Game Options', kind: 'info' });
       } else {
-        await message('Failed to reset game options.', { title: 'Reset Game Options', kind: 'error' });
+        await this.showSaveError(error, 'Reset Game Options');
       }
     }
   }
+
+  /** Field errors this page has an input for go next to it; the rest go in a dialog. */
+  private async showSaveError(error: OptionsError, title: string) {
+    this.fieldErrors = {};
+    if (error.kind === 'invalid') {
+      const unshown = error.errors.filter(e => {
+        const input = e.field.startsWith('vm_flags') ? 'vm_flags' : e.field;
+        if (!['max_ram', 'vm_flags', 'custom_java_path'].includes(input)) return true;
+        (this.fieldErrors[input] ??= []).push(e.message);
+        return false;
+      });
+      if (unshown.length === 0) return;
+      error = { kind: 'invalid', errors: unshown };
+    }
+    await message(describeOptionsError(error), { title, kind: 'error' });
+  }
 }
\ No newline at end of file
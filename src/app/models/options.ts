@@ -1,4 +1,5 @@
 export interface LauncherOptions {
+    schema_version?: number;
     launcher_dir?: string;
     game_dir?: string;
     init_on_start: boolean;
@@ -10,8 +11,55 @@ export interface LauncherOptions {
 }
 
 export interface GameOptions {
+    schema_version?: number;
     max_ram: number;
     vm_flags: string[];
     garbage_collector: string;
     custom_java_path: string;
 }
+
+/** A curated JVM configuration from `get_jvm_presets`. */
+export interface JvmPreset {
+    id: string;
+    name: string;
+    description: string;
+    garbage_collector: string;
+    max_ram: number | null;
+    flags: string[];
+}
+
+export type FlagCategory = 'memory' | 'gc' | 'diagnostics' | 'system_property' | 'other';
+
+/** How a configured flag is treated, from `describe_jvm_flags`. */
+export interface FlagInfo {
+    flag: string;
+    category: FlagCategory;
+    /** `false` when the flag is left out for the selected garbage collector. */
+    active: boolean;
+}
+
+/** A rejected option, keyed by its path in the options JSON (`max_ram`, `vm_flags[2]`). */
+export interface FieldError {
+    field: string;
+    message: string;
+}
+
+/** Why `save_options` / `save_game_options` did not save. */
+export type OptionsError =
+    | { kind: 'invalid'; errors: FieldError[] }
+    | { kind: 'io'; message: string };
+
+/** One line per problem, for a dialog. */
+export function describeOptionsError(error: OptionsError): string {
+    if (error.kind === 'io') return error.message;
+    return error.errors.map(e => e.message).join('\n');
+}
+
+/** Payload of the `options-changed` event, sent when an options file is edited outside the launcher. */
+export interface OptionsChanged {
+    file: string;
+    options: LauncherOptions | null;
+    gameOptions: GameOptions | null;
+    /** When set, the edit was rejected and the current options were kept. */
+    errors: FieldError[];
+}
@@ -0,0 +1,49 @@
+import { Injectable, NgZone } from '@angular/core';
+import { Router } from '@angular/router';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ask } from '@tauri-apps/plugin-dialog';
+import { BehaviorSubject } from 'rxjs';
+
+export type DeepLinkAction =
+  | { action: 'launch'; profile: string; offline: boolean }
+  | { action: 'redeem'; code: string };
+
+/** Asks the user to confirm `permadeath://` links and hands them to the right page */
+@Injectable({ providedIn: 'root' })
+export class DeepLinkService {
+  /** Confirmed launch request (offline flag), consumed by the play page */
+  readonly launch$ = new BehaviorSubject<{ offline: boolean } | null>(null);
+  /** Confirmed invitation code, consumed by the account page */
+  readonly inviteCode$ = new BehaviorSubject<string | null>(null);
+
+  constructor(private router: Router, private ngZone: NgZone) {}
+
+  async init(): Promise<void> {
+    await listen<DeepLinkAction>('deep-link', () => this.handlePending());
+    // A link that started the launcher arrived before we were listening
+    await this.handlePending();
+  }
+
+  private async handlePending(): Promise<void> {
+    const link = await invoke<DeepLinkAction | null>('take_pending_deep_link');
+    if (!link) return;
+
+    if (link.action === 'launch') {
+      const mode = link.offline ? ' in offline mode' : '';
+      const confirmed = await ask(`A link wants to launch the game${mode}. Start playing now?`, {
+        title: 'Join now', kind: 'info',
+      });
+      if (!confirmed) return;
+      await this.ngZone.run(() => this.router.navigate(['/play']));
+      this.launch$.next({ offline: link.offline });
+    } else {
+      const confirmed = await ask(`A link contains the invitation code "${link.code}". Create an account with it?`, {
+        title: 'Invitation', kind: 'info',
+      });
+      if (!confirmed) return;
+      await this.ngZone.run(() => this.router.navigate(['/account']));
+      this.inviteCode$.next(link.code);
+    }
+  }
+}
@@ -1,13 +1,20 @@
 import { Injectable } from '@angular/core';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { exit } from '@tauri-apps/plugin-process';
-import {GameOptions, LauncherOptions} from "@app/models/options";
+import { message } from '@tauri-apps/plugin-dialog';
+import { Subject } from 'rxjs';
+import {GameOptions, LauncherOptions, OptionsChanged, OptionsError} from "@app/models/options";
 
 @Injectable({ providedIn: 'root' })
 export class TauriService {
     options: LauncherOptions | null = null;
     gameOptions: GameOptions | null = null;
 
+    /** Fires after the options were reloaded because a file changed on disk. */
+    readonly optionsReloaded = new Subject<void>();
+    private watching = false;
+
     async initOptions() {
         try {
             this.options = await invoke<LauncherOptions>('read_options');
@@ -16,17 +23,47 @@ export class TauriService {
                 return;
             }
             this.gameOptions = await invoke<GameOptions>('read_game_options', { launcherOptions: this.options });
+            await this.watchOptions();
         } catch (error) {
             console.error("Error loading options:", error);
             await exit(1);
         }
     }
 
-    async saveOptions() {
-        return await invoke('save_options', { options: this.options });
+    /** Applies edits made to the options files outside the launcher, so a later save keeps them. */
+    private async watchOptions() {
+        if (this.watching) return;
+        this.watching = true;
+        await listen<OptionsChanged>('options-changed', async e => {
+            const change = e.payload;
+            if (change.errors.length > 0) {
+                const problems = change.errors.map(err => err.message).join('\n');
+                await message(`${change.file} was edited but not applied:\n${problems}`, { title: 'Settings', kind: 'warning' });
+                return;
+            }
+            if (change.options) this.options = change.options;
+            if (change.gameOptions) this.gameOptions = change.gameOptions;
+            this.optionsReloaded.next();
+        });
     }
 
-    async saveGameOptions() {
-        return await invoke('save_game_options', { gameOptions: this.gameOptions, launcherOptions: this.options });
+    /** Returns why the options were not saved, or `null` once they are. */
+    async saveOptions(): Promise<OptionsError | null> {
+        try {
+            await invoke('save_options', { options: this.options });
+            return null;
+        } catch (error) {
+            return error as OptionsError;
+        }
+    }
+
+    /** Returns why the game options were not saved, or `null` once they are. */
+    async saveGameOptions(): Promise<OptionsError | null> {
+        try {
+            await invoke('save_game_options', { gameOptions: this.gameOptions, launcherOptions: this.options });
+            return null;
+        } catch (error) {
+            return error as OptionsError;
+        }
     }
 }
\ No newline at end of file